@@ -0,0 +1,192 @@
+// src/attack_map.rs
+// Who attacks every square, occupied or empty. The exchangers only cover occupied
+// squares; castling, king escapes and square control also need the empty ones.
+// The map is rebuilt with the exchangers and updated after a pre-processed move
+// for only the pieces whose lines run through a square the move changed.
+//
+// An x-ray attacker is a slider that would attack the square if the first piece
+// in its way, of either side, were gone: a battery, a pin or a skewer.
+use crate::attacks::{piece_attacks, squares_of};
+use crate::board::{Board, Square};
+use crate::pieces::Side;
+
+fn side_index(side: Side) -> usize {
+    match side {
+        Side::White => 0,
+        Side::Black => 1,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttackMap {
+    /// Per target square and side, the squares of the pieces attacking it.
+    attackers: [[u64; 2]; 64],
+    /// Per target square and side, the squares of the sliders x-raying it.
+    xrays: [[u64; 2]; 64],
+    /// Per source square, what its piece attacks directly and by x-ray; kept so
+    /// an update can take back a piece's old contribution.
+    direct_from: [u64; 64],
+    xray_from: [u64; 64],
+    /// The source squares holding white pieces when their sets were recorded.
+    white_sources: u64,
+}
+
+impl Default for AttackMap {
+    fn default() -> Self {
+        AttackMap {
+            attackers: [[0; 2]; 64],
+            xrays: [[0; 2]; 64],
+            direct_from: [0; 64],
+            xray_from: [0; 64],
+            white_sources: 0,
+        }
+    }
+}
+
+/// What the piece on `square` attacks, directly and by x-ray, on the given board.
+fn piece_sets(board: &Board, square: Square) -> Option<(Side, u64, u64)> {
+    let piece = board.get_piece_on(square)?;
+    let data = piece.get_piece_data().ok()?;
+    let occupied = board.get_occupied_bitboard();
+    let direct = piece_attacks(data, square, occupied);
+    let xray = if data.is_sliding {
+        // look through the first blockers to the next piece on each line
+        piece_attacks(data, square, occupied & !direct) & !direct
+    } else {
+        0
+    };
+    Some((piece.get_side(), direct, xray))
+}
+
+impl AttackMap {
+    pub fn build(board: &Board) -> AttackMap {
+        let mut map = AttackMap::default();
+        for square in squares_of(board.get_occupied_bitboard()) {
+            if let Some((side, direct, xray)) = piece_sets(board, square) {
+                map.add(square, side, direct, xray);
+            }
+        }
+        map
+    }
+
+    /// Bitboard of the pieces of side `by` attacking `square`.
+    pub fn attackers(&self, square: Square, by: Side) -> u64 {
+        self.attackers[square.index()][side_index(by)]
+    }
+
+    /// Bitboard of the sliders of side `by` that attack `square` through one piece.
+    pub fn xray_attackers(&self, square: Square, by: Side) -> u64 {
+        self.xrays[square.index()][side_index(by)]
+    }
+
+    pub fn is_attacked(&self, square: Square, by: Side) -> bool {
+        self.attackers(square, by) != 0
+    }
+
+    /// Bitboard of the squares the piece on `square` attacks, empty if there is none.
+    pub fn attacks_from(&self, square: Square) -> u64 {
+        self.direct_from[square.index()]
+    }
+
+    /// Brings the map up to date after the pieces on the `changed` squares moved,
+    /// appeared or disappeared. Only those pieces and the sliders whose direct or
+    /// x-ray lines reach a changed square are recomputed: a piece beyond the second
+    /// blocker of a line cannot change what the slider sees.
+    pub(crate) fn update(&mut self, board: &Board, changed: u64) {
+        let mut dirty = changed;
+        for index in 0..64 {
+            if (self.direct_from[index] | self.xray_from[index]) & changed != 0 {
+                dirty |= 1u64 << index;
+            }
+        }
+        for square in squares_of(dirty) {
+            self.remove(square);
+            if let Some((side, direct, xray)) = piece_sets(board, square) {
+                self.add(square, side, direct, xray);
+            }
+        }
+    }
+
+    fn add(&mut self, source: Square, side: Side, direct: u64, xray: u64) {
+        let (index, bit) = (source.index(), 1u64 << source.index());
+        for target in squares_of(direct) {
+            self.attackers[target.index()][side_index(side)] |= bit;
+        }
+        for target in squares_of(xray) {
+            self.xrays[target.index()][side_index(side)] |= bit;
+        }
+        self.direct_from[index] = direct;
+        self.xray_from[index] = xray;
+        if side == Side::White {
+            self.white_sources |= bit;
+        }
+    }
+
+    fn remove(&mut self, source: Square) {
+        let (index, bit) = (source.index(), 1u64 << source.index());
+        let side = side_index(if self.white_sources & bit != 0 { Side::White } else { Side::Black });
+        for target in squares_of(self.direct_from[index]) {
+            self.attackers[target.index()][side] &= !bit;
+        }
+        for target in squares_of(self.xray_from[index]) {
+            self.xrays[target.index()][side] &= !bit;
+        }
+        self.direct_from[index] = 0;
+        self.xray_from[index] = 0;
+        self.white_sources &= !bit;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square::*;
+
+    fn bits(squares: &[Square]) -> u64 {
+        squares.iter().fold(0, |bits, square| bits | 1u64 << square.index())
+    }
+
+    #[test]
+    fn test_attackers_and_xrays_of_empty_squares() {
+        // the e2 knight is pinned by the e7 rook with the e8 queen behind it
+        let board = Board::named("pinned_knight").unwrap();
+        let map = board.attack_map().unwrap();
+        assert_eq!(map.attackers(e4, Side::Black), bits(&[e7]));
+        assert_eq!(map.xray_attackers(e4, Side::Black), bits(&[e8]));
+        assert_eq!(map.attackers(e1, Side::Black), 0);
+        assert_eq!(map.xray_attackers(e1, Side::Black), bits(&[e7]));
+        // the d4 knight is defended by the pawn and the other knight, f4 by that knight alone
+        assert_eq!(map.attackers(d4, Side::White), bits(&[c3, e2]));
+        assert!(map.is_attacked(f4, Side::White) && !map.is_attacked(h4, Side::White));
+        assert_eq!(map.attacks_from(c3), bits(&[b4, d4]));
+        assert_eq!(map.attacks_from(e5), 0);
+    }
+
+    #[test]
+    fn test_attack_map_follows_pre_processed_moves() {
+        let board = Board::named("double_discovered_check").unwrap();
+        let mut full = board.clone();
+        let mut incremental = board;
+        for san in ["Bf1+", "b5", "axb6"] {
+            let mv = full.parse_san(san).unwrap();
+            full = full.full_process(mv.from, mv.to, None).unwrap();
+            incremental = incremental.pre_processed_move(mv.from, mv.to).unwrap();
+            assert_eq!(incremental.attack_map(), full.attack_map(), "after {san}");
+            assert_eq!(incremental.attack_map(), Some(&AttackMap::build(&incremental)), "after {san}");
+        }
+        // the en passant capture opened both the a-file and the f1 bishop's diagonal
+        let map = full.attack_map().unwrap();
+        assert_eq!(map.attackers(a6, Side::White), bits(&[a1, f1]));
+        assert_eq!(map.attackers(b5, Side::Black), bits(&[a6]));
+    }
+
+    #[test]
+    fn test_stripped_boards_carry_no_attack_map() {
+        let board = Board::named("pinned_knight").unwrap();
+        let stripped = board.without_exchangers();
+        assert!(stripped.attack_map().is_none());
+        let mv = stripped.parse_san("Kd1").unwrap();
+        assert!(stripped.play_unprocessed(mv).unwrap().attack_map().is_none());
+        assert!(board.play_unprocessed(mv).unwrap().attack_map().is_none());
+    }
+}
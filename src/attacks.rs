@@ -0,0 +1,119 @@
+// src/attacks.rs
+// Square attack queries built on the ray and magic tables. Unlike the exchangers,
+// these work for any square, occupied or not.
+use crate::board::{Board, Square};
+use crate::compass_groups::Direction;
+use crate::magic_bitboards::{bishop_attacks, queen_attacks, rook_attacks};
+use crate::occupied_squares::RAYS;
+use crate::pieces::{BasicPieceType, PieceTypeData, Side};
+
+/// The squares a piece of the given type attacks from `square`. Pawns attack only
+/// their two forward diagonals, never the square in front of them.
+pub fn piece_attacks(piece_data: &PieceTypeData, square: Square, occupied: u64) -> u64 {
+    match piece_data.basic_piece_type {
+        BasicPieceType::Rook => rook_attacks(square, occupied),
+        BasicPieceType::Bishop => bishop_attacks(square, occupied),
+        BasicPieceType::Queen => queen_attacks(square, occupied),
+        BasicPieceType::Pawn => {
+            let diagonals = match piece_data.side {
+                Side::White => [Direction::NE, Direction::NW],
+                Side::Black => [Direction::SE, Direction::SW],
+            };
+            diagonals.iter().fold(0u64, |bits, d| bits | step(square, *d))
+        }
+        BasicPieceType::Knight | BasicPieceType::King => {
+            piece_data.directions.iter().fold(0u64, |bits, d| bits | step(square, *d))
+        }
+    }
+}
+
+fn step(square: Square, direction: Direction) -> u64 {
+    square.offset(direction).map_or(0, |sq| 1u64 << sq.index())
+}
+
+/// Bitboard of the pieces of side `by` that attack `target`, given an occupancy
+/// (usually the board's own, but callers may pass a hypothetical one).
+pub fn attackers_of_with(board: &Board, target: Square, by: Side, occupied: u64) -> u64 {
+    let target_bit = 1u64 << target.index();
+    let mut attackers = 0u64;
+    for (square, piece) in board.iter_pieces() {
+        if piece.get_side() != by || occupied & (1u64 << square.index()) == 0 {
+            continue;
+        }
+        if let Ok(data) = piece.get_piece_data()
+            && piece_attacks(data, *square, occupied) & target_bit != 0
+        {
+            attackers |= 1u64 << square.index();
+        }
+    }
+    attackers
+}
+
+pub fn attackers_of(board: &Board, target: Square, by: Side) -> u64 {
+    attackers_of_with(board, target, by, board.get_occupied_bitboard())
+}
+
+pub fn is_attacked(board: &Board, target: Square, by: Side) -> bool {
+    attackers_of(board, target, by) != 0
+}
+
+/// Bitboard of every square attacked by side `by`.
+pub fn attacked_squares(board: &Board, by: Side) -> u64 {
+    let occupied = board.get_occupied_bitboard();
+    board
+        .iter_pieces()
+        .filter(|(_, piece)| piece.get_side() == by)
+        .filter_map(|(square, piece)| piece.get_piece_data().ok().map(|data| piece_attacks(data, *square, occupied)))
+        .fold(0u64, |bits, attacks| bits | attacks)
+}
+
+/// Squares strictly between two squares on a shared line, or 0 if they share none.
+pub fn between(from: Square, to: Square) -> u64 {
+    for (d, ray) in RAYS[from.index()].iter().enumerate() {
+        if ray & (1u64 << to.index()) != 0 {
+            let direction = crate::compass_groups::ALL_DIRECTIONS[d];
+            if direction.is_half_wind() {
+                return 0;
+            }
+            return ray & !RAYS[to.index()][d] & !(1u64 << to.index());
+        }
+    }
+    0
+}
+
+/// Iterates the squares of a bitboard in index order.
+pub fn squares_of(mut bits: u64) -> impl Iterator<Item = Square> {
+    std::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+        let idx = bits.trailing_zeros() as usize;
+        bits &= bits - 1;
+        Square::from_index(idx)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square::*;
+
+    #[test]
+    fn test_attackers_of_occupied_and_empty_squares() {
+        let mut board = Board::new();
+        board.init_from_pid_list(vec!["e1K", "e8k", "d4P", "f6n", "a4R", "b7b"]).unwrap();
+
+        let white_on_e5 = attackers_of(&board, e5, Side::White);
+        assert_eq!(white_on_e5, 1u64 << d4.index());
+        // the b7 bishop reaches e4 across the empty c6 and d5
+        let black_on_e4 = attackers_of(&board, e4, Side::Black);
+        assert_eq!(black_on_e4, (1u64 << f6.index()) | (1u64 << b7.index()));
+        assert!(!is_attacked(&board, h4, Side::White));
+        assert!(is_attacked(&board, c4, Side::White));
+        // the d4 pawn stops the rook's ray
+        assert!(!is_attacked(&board, e4, Side::White));
+        assert_eq!(between(a4, d4), (1u64 << b4.index()) | (1u64 << c4.index()));
+        assert_eq!(between(e1, g2), 0);
+        assert_eq!(squares_of(white_on_e5).collect::<Vec<_>>(), vec![d4]);
+    }
+}
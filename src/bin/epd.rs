@@ -0,0 +1,42 @@
+// Runs an EPD test suite: `epd <file.epd> [--depth N | --time-ms N]`.
+// Prints a per-position table and the solved count; exits non-zero on bad input.
+use neetroc_bot::epd::{parse_epd, run_suite};
+use neetroc_bot::logging;
+use neetroc_bot::search::SearchLimits;
+use std::process::ExitCode;
+use std::time::Duration;
+
+fn usage() -> ExitCode {
+    eprintln!("usage: epd <file.epd> [--depth N | --time-ms N]");
+    ExitCode::from(2)
+}
+
+fn main() -> ExitCode {
+    logging::init_from_env(log::LevelFilter::Warn);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(path) = args.first() else { return usage() };
+    let limits = match (args.get(1).map(String::as_str), args.get(2).and_then(|n| n.parse::<u64>().ok())) {
+        (None, _) => SearchLimits::time(Duration::from_secs(1)),
+        (Some("--depth"), Some(depth)) => SearchLimits::depth(depth as u32),
+        (Some("--time-ms"), Some(millis)) => SearchLimits::time(Duration::from_millis(millis)),
+        _ => return usage(),
+    };
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("cannot read {path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let report = match parse_epd(&text).map(|records| run_suite(&records, limits)) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("{path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("{report}");
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,52 @@
+// Runs a directory of scenario files: `scenarios <dir>`.
+// Every `*.scn` file is played through both move paths; prints PASS/FAIL per
+// scenario and a summary, and exits non-zero if any scenario fails or cannot be read.
+use neetroc_bot::logging;
+use neetroc_bot::scenario::{parse_scenario, run_scenario};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    logging::init_from_env(log::LevelFilter::Warn);
+
+    let Some(dir) = std::env::args().nth(1) else {
+        eprintln!("usage: scenarios <dir>");
+        return ExitCode::from(2);
+    };
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "scn"))
+            .collect(),
+        Err(err) => {
+            eprintln!("cannot read {dir}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    paths.sort();
+
+    let (mut passed, mut failed) = (0, 0);
+    for path in &paths {
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let scenario = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| parse_scenario(&text, &stem).map_err(|err| err.to_string()));
+        match scenario {
+            Ok(scenario) => {
+                let outcome = run_scenario(&scenario);
+                println!("{outcome}");
+                if outcome.passed() {
+                    passed += 1;
+                } else {
+                    failed += 1;
+                }
+            }
+            Err(err) => {
+                println!("FAIL {stem}\n    {}: {err}", path.display());
+                failed += 1;
+            }
+        }
+    }
+    println!("{passed} passed, {failed} failed");
+    if failed == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
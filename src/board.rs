@@ -1,12 +1,19 @@
 use crate::{Side, CARDINALS};
-use crate::compass_groups::{Direction, DIRECTION_PAIRS, HALF_WINDS, VERTICALS, get_direction};
-use crate::occupied_squares::{bit_to_string_square, generate_ray_path, square_to_bit, get_next_sqid, first_occpd_square};
-use crate::pid::Pid;
+use crate::compass_groups::{Direction, DIRECTION_PAIRS, HALF_WINDS, HORIZONTALS, VERTICALS, get_direction};
+use crate::occupied_squares::{bit_to_string_square, generate_ray_path, square_to_bit, first_occpd_square, NEIGHBOURS};
+use crate::attack_map::AttackMap;
+use crate::attacks::piece_attacks;
+use crate::diagram::DiagramOptions;
+use crate::error::{Error, Result};
+use crate::magic_bitboards::first_blocker;
+use crate::moves::Move;
+use crate::legality::check_position;
+use crate::pid::{split_chain, Pid};
+use crate::processing_stats::{MoveTiming, ProcessingPath, ProcessingStats};
 use crate::pieces::BasicPieceType;
 use crate::pieces::Piece;
 use crate::pieces::PieceType;
 use crate::pieces::PieceTypeData;
-use crate::pieces::King_Locations;
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Write; // for write! macro
@@ -14,6 +21,7 @@ use std::str::FromStr;
 use strum::{AsRefStr, Display, EnumString};
 use strum::{EnumIter, IntoEnumIterator};
 use std::time::{Duration, Instant};
+use log::{debug, trace, warn};
 
 #[derive(Debug, EnumIter, PartialEq, Eq, Hash, Copy, Clone)] // These are useful traits to derive
 #[derive(Display, AsRefStr, EnumString)]
@@ -85,16 +93,221 @@ pub enum Square {
     h8,
 }
 
+/// Every square in index order, so that `ALL_SQUARES[sq as usize] == sq`.
+pub const ALL_SQUARES: [Square; 64] = {
+    use Square::*;
+    [
+        a1, b1, c1, d1, e1, f1, g1, h1,
+        a2, b2, c2, d2, e2, f2, g2, h2,
+        a3, b3, c3, d3, e3, f3, g3, h3,
+        a4, b4, c4, d4, e4, f4, g4, h4,
+        a5, b5, c5, d5, e5, f5, g5, h5,
+        a6, b6, c6, d6, e6, f6, g6, h6,
+        a7, b7, c7, d7, e7, f7, g7, h7,
+        a8, b8, c8, d8, e8, f8, g8, h8,
+    ]
+};
+
+#[derive(Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub enum File { A, B, C, D, E, F, G, H }
+
+#[derive(Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub enum Rank { R1, R2, R3, R4, R5, R6, R7, R8 }
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum SquareColour { Dark, Light }
+
+const ALL_FILES: [File; 8] = [File::A, File::B, File::C, File::D, File::E, File::F, File::G, File::H];
+const ALL_RANKS: [Rank; 8] = [Rank::R1, Rank::R2, Rank::R3, Rank::R4, Rank::R5, Rank::R6, Rank::R7, Rank::R8];
+
+impl File {
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    pub const fn from_index(index: usize) -> Option<File> {
+        if index < 8 { Some(ALL_FILES[index]) } else { None }
+    }
+
+    pub const fn from_char(c: char) -> Option<File> {
+        match c {
+            'a'..='h' => File::from_index(c as usize - 'a' as usize),
+            _ => None,
+        }
+    }
+
+    pub const fn to_char(self) -> char {
+        (b'a' + self as u8) as char
+    }
+}
+
+impl Rank {
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    pub const fn from_index(index: usize) -> Option<Rank> {
+        if index < 8 { Some(ALL_RANKS[index]) } else { None }
+    }
+
+    pub const fn from_char(c: char) -> Option<Rank> {
+        match c {
+            '1'..='8' => Rank::from_index(c as usize - '1' as usize),
+            _ => None,
+        }
+    }
+
+    pub const fn to_char(self) -> char {
+        (b'1' + self as u8) as char
+    }
+}
+
+impl Square {
+    /// Bit position of the square on a bitboard (a1 = 0 .. h8 = 63).
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    pub const fn from_index(index: usize) -> Option<Square> {
+        if index < 64 { Some(ALL_SQUARES[index]) } else { None }
+    }
+
+    pub const fn from_file_rank(file: File, rank: Rank) -> Square {
+        ALL_SQUARES[rank.index() * 8 + file.index()]
+    }
+
+    /// Parses a file and rank character pair such as ('e', '4').
+    pub const fn from_chars(file: char, rank: char) -> Option<Square> {
+        match (File::from_char(file), Rank::from_char(rank)) {
+            (Some(file), Some(rank)) => Some(Square::from_file_rank(file, rank)),
+            _ => None,
+        }
+    }
+
+    pub const fn file(self) -> File {
+        ALL_FILES[self as usize % 8]
+    }
+
+    pub const fn rank(self) -> Rank {
+        ALL_RANKS[self as usize / 8]
+    }
+
+    /// King-move (Chebyshev) distance between two squares.
+    pub const fn distance(self, other: Square) -> u8 {
+        let file_diff = (self.file() as i8 - other.file() as i8).unsigned_abs();
+        let rank_diff = (self.rank() as i8 - other.rank() as i8).unsigned_abs();
+        if file_diff > rank_diff { file_diff } else { rank_diff }
+    }
+
+    pub const fn colour(self) -> SquareColour {
+        if (self.file() as usize + self.rank() as usize).is_multiple_of(2) {
+            SquareColour::Dark
+        } else {
+            SquareColour::Light
+        }
+    }
+
+    /// The square one step away in the given direction, if it is still on the board.
+    pub const fn offset(self, direction: Direction) -> Option<Square> {
+        NEIGHBOURS[self as usize][direction as usize]
+    }
+
+    /// For an en passant capture square, the square of the pawn that gets captured.
+    pub const fn en_passant_victim(self) -> Square {
+        let rank = if matches!(self.rank(), Rank::R6) { Rank::R5 } else { Rank::R4 };
+        Square::from_file_rank(self.file(), rank)
+    }
+}
+
+/// How the side to move came to be in check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckKind {
+    /// The piece that moved attacks the king.
+    Direct,
+    /// The piece that moved uncovered a line from one of its own sliders to the king.
+    Discovered,
+    /// Two checkers at once, so only a king move can answer it.
+    Double,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+impl CastlingRights {
+    pub const NONE: CastlingRights = CastlingRights {
+        white_king_side: false,
+        white_queen_side: false,
+        black_king_side: false,
+        black_queen_side: false,
+    };
+    pub const ALL: CastlingRights = CastlingRights {
+        white_king_side: true,
+        white_queen_side: true,
+        black_king_side: true,
+        black_queen_side: true,
+    };
+
+    /// Parses the FEN castling field, e.g. "KQkq", "Kq" or "-".
+    pub fn from_fen(field: &str) -> Option<CastlingRights> {
+        if field == "-" {
+            return Some(CastlingRights::NONE);
+        }
+        let mut rights = CastlingRights::NONE;
+        for c in field.chars() {
+            let right = match c {
+                'K' => &mut rights.white_king_side,
+                'Q' => &mut rights.white_queen_side,
+                'k' => &mut rights.black_king_side,
+                'q' => &mut rights.black_queen_side,
+                _ => return None,
+            };
+            if *right {
+                return None;
+            }
+            *right = true;
+        }
+        if field.is_empty() { None } else { Some(rights) }
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut field = String::new();
+        for (held, c) in [
+            (self.white_king_side, 'K'),
+            (self.white_queen_side, 'Q'),
+            (self.black_king_side, 'k'),
+            (self.black_queen_side, 'q'),
+        ] {
+            if held {
+                field.push(c);
+            }
+        }
+        if field.is_empty() { "-".to_string() } else { field }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Board {
     pieces: HashMap<Square, Piece>,
     occupied: u64,
-    moves: Vec<(Square, Square)>, // representation of pieces as bits in 8 bytes according to piece position
+    moves: Vec<Move>, // representation of pieces as bits in 8 bytes according to piece position
     turn: Side,
     checks: Vec<Pid>,
+    check_kind: Option<CheckKind>,
     capture_square_en_passant: Option<Square>,
     white_king_location: Option<Square>,
     black_king_location: Option<Square>,
+    castling: CastlingRights,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    stats: ProcessingStats,
+    /// Built with the exchangers; boxed so boards without it, the stripped ones the
+    /// search plays on, stay small.
+    attack_map: Option<Box<AttackMap>>,
 }
 
 // impl std::fmt::Debug for Board {
@@ -106,6 +319,35 @@ pub struct Board {
 //     }
 // }
 
+/// The square of the first piece in an exchanger chain, past any pin marker.
+fn chain_head(chain: &str) -> Result<Square> {
+    let (_, pids) = split_chain(chain)?;
+    Ok(pids[0].square())
+}
+
+/// The exchanger chains that differ between the two boards, per square and direction;
+/// a chain now held by another piece counts as changed. Both move paths report this
+/// count, so their figures compare.
+fn changed_chains(before: &Board, after: &Board) -> usize {
+    let chains = |board: &Board| -> HashMap<(Square, Direction), (String, String)> {
+        board
+            .pieces
+            .iter()
+            .flat_map(|(square, piece)| {
+                piece.exchangers.iter().map(move |(drctn, chain)| ((*square, *drctn), (piece.pid.to_string(), chain.clone())))
+            })
+            .collect()
+    };
+    let (old, new) = (chains(before), chains(after));
+    old.keys().filter(|key| !new.contains_key(key)).count()
+        + new.iter().filter(|(key, value)| old.get(key) != Some(value)).count()
+}
+
+/// A square named in a ray path from `occupied_squares`.
+fn ray_square(sq: &str) -> Result<Square> {
+    Square::from_str(sq).map_err(|_| Error::InconsistentExchangers(format!("ray path names '{sq}', which is not a square")))
+}
+
 impl Default for Board {
     fn default() -> Self {
         Self::new()
@@ -120,35 +362,47 @@ impl Board {
             moves: [].to_vec(),
             turn:  Side::White,
             checks: [].to_vec(),
+            check_kind: None,
             capture_square_en_passant: None,
             white_king_location: None,
             black_king_location: None,
+            castling: CastlingRights::NONE,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            stats: ProcessingStats::default(),
+            attack_map: None,
         }
     }
 
     pub fn place_piece(&mut self, piece: Piece) {
         let square = piece.get_square();
-        self.pieces.insert(square, piece);
+        let pchar = piece.get_piece_type_as_char();
+        if let Some(replaced) = self.pieces.insert(square, piece) {
+            self.forget_king(&replaced);
+        }
+        match pchar {
+            'K' => self.white_king_location = Some(square),
+            'k' => self.black_king_location = Some(square),
+            _ => {}
+        }
         let bit = square_to_bit(square);
         self.occupied |= 1u64 << bit;
     }
 
-    pub fn create_and_place_piece(&mut self, piece_identifier: &str) {
-        match Pid::new(piece_identifier) {
-            Ok(pid) => {
-                let piece = Piece::new(pid);
-                let sq = &piece_identifier[0..2];
-                let square = Square::from_str(sq).unwrap();
-                self.pieces.insert(square, piece);
-
-                let bit = square_to_bit(square);
-                self.occupied |= 1u64 << bit;
-            }
-            Err(e) => {
-                println!("Unable to create piece with identifier '{}': {}", piece_identifier, e);
-            }
+    fn forget_king(&mut self, piece: &Piece) {
+        let square = Some(piece.get_square());
+        match piece.get_piece_type_as_char() {
+            'K' if self.white_king_location == square => self.white_king_location = None,
+            'k' if self.black_king_location == square => self.black_king_location = None,
+            _ => {}
         }
     }
+
+    pub fn create_and_place_piece(&mut self, piece_identifier: &str) -> Result<()> {
+        let pid = Pid::new(piece_identifier)?;
+        self.place_piece(Piece::new(pid));
+        Ok(())
+    }
     pub fn updates_per_piece( // GATHER THE EXCHANGERS FOR THE PIECE
         &self,
         square: &Square,
@@ -186,7 +440,7 @@ impl Board {
         }
         updates
     }
-    pub fn build_all_xchngrs(&mut self) {
+    pub fn build_all_xchngrs(&mut self) -> Result<()> {
         // First collect all the paths and directions we need to process
         let mut updates: Vec<(Square, Direction, String)> = Vec::new();
         let mut piece_info_to_process: Vec<(Square, &'static PieceTypeData)> = Vec::new();
@@ -194,7 +448,7 @@ impl Board {
         // First pass: Collect necessary immutable data from pieces.
         // This avoids holding an immutable borrow on `self.pieces` while we need to mutate it.
         for (square, piece) in &self.pieces {
-            let piece_data = piece.get_piece_data();
+            let piece_data = piece.get_piece_data()?;
             piece_info_to_process.push((*square, piece_data));
         }
 
@@ -220,6 +474,8 @@ impl Board {
                 }
             }
         }
+        self.attack_map = Some(Box::new(AttackMap::build(self)));
+        Ok(())
     }
 
     fn extract_pid_seq(
@@ -250,7 +506,10 @@ impl Board {
 
             while ulmt <= sqid_seq_len {
                 let sq = &sqid_seq[llmt..ulmt];
-                let square = Square::from_str(sq).unwrap();
+                let Ok(square) = Square::from_str(sq) else {
+                    warn!("ray path {sqid_seq} holds '{sq}', which is not a square");
+                    break;
+                };
                 let piece_opt = &self.pieces.get(&square);
                 match piece_opt  {
                     Some(piece) => {
@@ -268,7 +527,6 @@ impl Board {
                                         if focus_piece_data.basic_piece_type == BasicPieceType::Pawn
                                                 && focus_piece_data.side != xr_data.side
                                                     && self.capture_square_en_passant.is_some() {
-                                            let epcapture_square = self.capture_square_en_passant.unwrap();
                                             // we need the focus piece square (via pid) to check files are adjacent
                                             // println!("Pawn focal piece - ep capture square: {:?}", self.capture_square_en_passant);
                                             return Board::extract_pin_seq(self,focus_piece_data, sqid_seq, drctn);
@@ -302,7 +560,9 @@ impl Board {
                         }
                     }
                     None => {
-                        println!("No matching piece");
+                        // the occupied bitboard and the pieces map disagree; stop rather than spin
+                        warn!("no piece on {square} although the ray path {sqid_seq} lists it");
+                        break;
                     }
                 }
             }
@@ -341,7 +601,10 @@ impl Board {
 
             while ulmt <= sqid_seq_len {
                 let sq = &sqid_seq[llmt..ulmt];
-                let square = Square::from_str(sq).unwrap();
+                let Ok(square) = Square::from_str(sq) else {
+                    warn!("ray path {sqid_seq} holds '{sq}', which is not a square");
+                    break;
+                };
                 let piece = &self.pieces.get(&square);
                 match piece {
                     Some(piece) => {
@@ -397,7 +660,9 @@ impl Board {
                         }
                     }
                     None => {
-                        println!("No matching piece");
+                        // the occupied bitboard and the pieces map disagree; stop rather than spin
+                        warn!("no piece on {square} although the ray path {sqid_seq} lists it");
+                        break;
                     }
                 }
             }
@@ -405,87 +670,173 @@ impl Board {
         if !pins.is_empty() { Some(pins) } else { None }
     }
 
-    pub fn full_process_move(&self, from: Square, to: Square) -> Board {
-        // assume a legal move - but some checks anyway
-        let start = Instant::now();
+    pub fn full_process_move(&self, from: Square, to: Square) -> Result<Board> {
+        self.full_process(from, to, None)
+    }
 
+    /// The king's two-square step when castling, mapped to the rook's move.
+    fn castling_rook_squares(pchar: char, from: Square, to: Square) -> Option<(Square, Square)> {
+        if !pchar.eq_ignore_ascii_case(&'K') || from.rank().index() != to.rank().index() || from.file().index().abs_diff(to.file().index()) != 2 {
+            return None;
+        }
+        let (rook_file, rook_to_file) = if to.file().index() > from.file().index() { (File::H, File::F) } else { (File::A, File::D) };
+        Some((Square::from_file_rank(rook_file, to.rank()), Square::from_file_rank(rook_to_file, to.rank())))
+    }
+
+    /// A pawn that reaches the last rank must say what it becomes.
+    fn check_promotion(piece: &Piece, from: Square, to: Square, promotion: Option<char>) -> Result<()> {
+        let last_rank = if piece.get_side() == Side::White { 7 } else { 0 };
+        if promotion.is_none() && piece.get_piece_type_as_char().eq_ignore_ascii_case(&'P') && to.rank().index() == last_rank {
+            return Err(Error::IllegalMove { from, to, reason: "a pawn reaching the last rank must promote".to_string() });
+        }
+        Ok(())
+    }
+
+    /// Moves the pieces on a copy of the board - the en passant victim, a castling
+    /// rook and any promotion included - and returns it with the moving piece's char.
+    /// Exchangers and status are left for the caller to bring up to date.
+    fn relocate(&self, from: Square, to: Square, promotion: Option<char>) -> Result<(Board, char)> {
         let mut new_board = self.clone();
         // let mut is_en_passant_capture = false;
         // let mut ep_square: Square;
-        if self.capture_square_en_passant.is_some() {
-            let ep_square = self.capture_square_en_passant.unwrap();
+        if let Some(ep_square) = self.capture_square_en_passant {
             if ep_square == to { 
                 // is_en_passant_capture = true;
-                let sqid_file = ep_square.to_string().chars().nth(0).unwrap();
-                let sqid_rank = ep_square.to_string().chars().nth(1).unwrap();
-
-                let new_rank;
-                if sqid_rank == '6' {
-                    new_rank = '5';
-                } else {
-                    new_rank = '4';
-                }
-
-                let ep_captured_pawn_sq = format!("{}{}", sqid_file, new_rank);
-                let ep_captured_square = Square::from_str(&ep_captured_pawn_sq).unwrap();
+                let ep_captured_square = ep_square.en_passant_victim();
                 new_board.remove_piece_from(ep_captured_square);
 
                 new_board.capture_square_en_passant = None;
             }
         }
 
-        let piece = self.get_piece_on(from).unwrap();
+        let piece = self.get_piece_on(from).ok_or(Error::EmptySquare(from))?;
+        Board::check_promotion(piece, from, to, promotion)?;
         let pchar = piece.get_piece_type_as_char();
-        let pid = format!("{}{}", to, pchar);
+        let landed_char = match promotion {
+            Some(promoted) if piece.get_side() == Side::White => promoted.to_ascii_uppercase(),
+            Some(promoted) => promoted.to_ascii_lowercase(),
+            None => pchar,
+        };
+        let pid = format!("{}{}", to, landed_char);
 
         new_board.remove_piece_from(from);
-        new_board.create_and_place_piece(&pid);
+        new_board.create_and_place_piece(&pid)?;
+        if let Some((rook_from, rook_to)) = Board::castling_rook_squares(pchar, from, to) {
+            let rook = new_board.get_piece_on(rook_from).ok_or(Error::EmptySquare(rook_from))?;
+            let rook_pid = format!("{}{}", rook_to, rook.get_piece_type_as_char());
+            new_board.remove_piece_from(rook_from);
+            new_board.create_and_place_piece(&rook_pid)?;
+        }
+        Ok((new_board, pchar))
+    }
+
+    /// Plays a move without touching any exchanger, for callers such as the search
+    /// that only need the pieces, the turn and the move status. Exchangers on the
+    /// returned board are stale unless the input had none, and it has no attack map.
+    pub(crate) fn play_unprocessed(&self, mv: Move) -> Result<Board> {
+        let resets_clock = self.is_capture(&mv) || self.get_piece_on(mv.from).is_some_and(|p| p.get_piece_type_as_char().eq_ignore_ascii_case(&'P'));
+        let (mut new_board, pchar) = self.relocate(mv.from, mv.to, mv.promotion)?;
+        new_board.attack_map = None;
+        new_board.moves.push(mv);
+        new_board.advance_clocks(resets_clock);
+        new_board.update_status(mv.from, mv.to, pchar);
+        Ok(new_board)
+    }
+
+    /// Copy of the board with every exchanger and the attack map dropped, cheap to
+    /// clone and to play `play_unprocessed` moves on.
+    pub(crate) fn without_exchangers(&self) -> Board {
+        let mut stripped = self.clone();
+        for piece in stripped.pieces.values_mut() {
+            piece.exchangers.clear();
+        }
+        stripped.attack_map = None;
+        stripped
+    }
+
+    /// Rebuilds every exchanger after the move. `promotion` names the piece a pawn
+    /// reaching the last rank becomes ('Q', 'R', 'B' or 'N', either case).
+    pub(crate) fn full_process(&self, from: Square, to: Square, promotion: Option<char>) -> Result<Board> {
+        self.rebuild_after(from, to, promotion, ProcessingPath::Full)
+    }
+
+    /// The work of `full_process`, with the timing recorded against `path`: a move
+    /// asked of the pre-processed path that falls back to the rebuild counts there.
+    fn rebuild_after(&self, from: Square, to: Square, promotion: Option<char>, path: ProcessingPath) -> Result<Board> {
+        // assume a legal move - but some checks anyway
+        let start = Instant::now();
+
+        let resets_clock = self.is_capture(&Move::new(from, to))
+            || self.get_piece_on(from).is_some_and(|p| p.get_piece_type_as_char().eq_ignore_ascii_case(&'P'));
+        let (mut new_board, pchar) = self.relocate(from, to, promotion)?;
 
-        new_board.build_all_xchngrs();
-        new_board.moves.push((from, to));
+        new_board.build_all_xchngrs()?;
+        new_board.moves.push(Move { from, to, promotion: promotion.map(|p| p.to_ascii_uppercase()) });
+        new_board.advance_clocks(resets_clock);
         new_board.update_status(from, to, pchar);
 
         let duration = start.elapsed();
-        println!("Fully processed move {from}-{to} took {} nanos.", duration.as_nanos());
+        let exchanger_updates = changed_chains(self, &new_board);
+        new_board.stats.record(MoveTiming {
+            from,
+            to,
+            path,
+            nanos: duration.as_nanos(),
+            exchanger_updates,
+        });
+        debug!("Fully processed move {from}-{to} took {} nanos.", duration.as_nanos());
+        new_board.debug_validate(from, to, Board::validate_structure)?;
+
+        Ok(new_board)
+    }
 
-        new_board
+    /// Updates only the exchangers the move can reach. Debug builds then check the
+    /// board against a full rebuild and report any difference as `CorruptBoard`.
+    pub fn pre_processed_move(&self, from: Square, to: Square) -> Result<Board> {
+        let prpsd_board = self.pre_processed_unvalidated(from, to)?;
+        prpsd_board.debug_validate(from, to, Board::validate)?;
+        Ok(prpsd_board)
     }
 
-    pub fn pre_processed_move(&self, from: Square, to: Square) -> Board {
+    /// `pre_processed_move` without the debug check, for the harnesses that compare
+    /// the two paths themselves and want the divergent board rather than an error.
+    pub(crate) fn pre_processed_unvalidated(&self, from: Square, to: Square) -> Result<Board> {
+        if let Some(piece) = self.get_piece_on(from)
+            && Board::castling_rook_squares(piece.get_piece_type_as_char(), from, to).is_some()
+        {
+            // two pieces move at once, which the incremental updates do not cover
+            debug!("Castling {from}-{to} falls back to full processing");
+            return self.rebuild_after(from, to, None, ProcessingPath::PreProcessed);
+        }
         let start = Instant::now();
 
         let mut xr_updates: Vec<(Square, Direction, Option<String>)> = Vec::new();
 
-        let from_piece = self.get_piece_on(from).unwrap();
+        let from_piece = self.get_piece_on(from).ok_or(Error::EmptySquare(from))?;
+        Board::check_promotion(from_piece, from, to, None)?;
         let pchar = from_piece.get_piece_type_as_char();
         let new_pid = format!("{}{}", to, pchar);
-        let new_piece = Piece::new(Pid::new(&new_pid).unwrap());
-        let move_drctn = get_direction(from, to)
-                .expect("pre_processed_move: surely a legal move must have a valid direction?");
+        let new_piece = Piece::new(Pid::new(&new_pid)?);
+        let move_drctn = get_direction(from, to).ok_or_else(|| Error::IllegalMove {
+            from,
+            to,
+            reason: "no compass direction joins the two squares".to_string(),
+        })?;
         let drctn_back = move_drctn.opposite();
-        println!("Pre_processed move '{from}-{to}', with pid '{new_pid:?}'");
+        trace!("Pre_processed move '{from}-{to}', with pid '{new_pid:?}'");
         // assume a legal move - but some checks anyway
 
+        let captured = self.get_piece_on(to).map(|piece| piece.get_pid().to_string());
         let mut prpsd_board = self.clone();
-        if self.capture_square_en_passant.is_some() {
-            let ep_square = self.capture_square_en_passant.unwrap();
+        let mut changed = (1u64 << from.index()) | (1u64 << to.index());
+        if let Some(ep_square) = self.capture_square_en_passant {
             if ep_square == to { 
-                
-                let sqid_file = ep_square.to_string().chars().nth(0).unwrap();
-                let sqid_rank = ep_square.to_string().chars().nth(1).unwrap();
-
-                let new_rank;
-                if sqid_rank == '6' {
-                    new_rank = '5';
-                } else {
-                    new_rank = '4';
-                }
-
-                let ep_captured = format!("{}{}", sqid_file, new_rank);
-                let ep_captured_square = Square::from_str(&ep_captured).unwrap();
-                let ep_captured_piece = self.get_piece_on(ep_captured_square).unwrap();
+                let ep_captured_square = ep_square.en_passant_victim();
+                let ep_captured_piece = self.get_piece_on(ep_captured_square)
+                        .ok_or(Error::EmptySquare(ep_captured_square))?;
                 prpsd_board.remove_piece_from(ep_captured_square);
-                prpsd_board.assess_vacated(ep_captured_piece.clone(), new_pid.clone(), &mut xr_updates);
+                changed |= 1u64 << ep_captured_square.index();
+                prpsd_board.assess_vacated(ep_captured_piece.clone(), new_pid.clone(), &mut xr_updates)?;
 
                 prpsd_board.capture_square_en_passant = None;
             }
@@ -494,8 +845,8 @@ impl Board {
         prpsd_board.remove_piece_from(from);
         prpsd_board.place_piece(new_piece);
 
-        prpsd_board.assess_vacated(from_piece.clone(), new_pid.clone(), & mut xr_updates);
-        prpsd_board.assess_landed(new_pid.clone(), drctn_back, & mut xr_updates);
+        prpsd_board.assess_vacated(from_piece.clone(), new_pid.clone(), & mut xr_updates)?;
+        prpsd_board.assess_landed(new_pid.clone(), drctn_back, & mut xr_updates)?;
 
         // Sort updates by Direction enum before processing
         xr_updates.sort_by(|a, b| a.1.cmp(&b.1));
@@ -513,45 +864,61 @@ impl Board {
             }
         }
 
-        prpsd_board.moves.push((from, to));
+        if let Some(captured) = captured {
+            prpsd_board.forget_captured(&captured);
+        }
+        prpsd_board.update_attack_map(changed);
+
+        prpsd_board.moves.push(Move::new(from, to));
+        prpsd_board.advance_clocks(pchar.eq_ignore_ascii_case(&'P') || self.is_square_occupied(to));
         prpsd_board.update_status(from, to, pchar);
 
         let duration = start.elapsed();
-        println!("Efficiently processed move {from}-{to} took {} nanos.", duration.as_nanos());
-
-        prpsd_board
+        let exchanger_updates = changed_chains(self, &prpsd_board);
+        prpsd_board.stats.record(MoveTiming {
+            from,
+            to,
+            path: ProcessingPath::PreProcessed,
+            nanos: duration.as_nanos(),
+            exchanger_updates,
+        });
+        debug!("Efficiently processed move {from}-{to} took {} nanos.", duration.as_nanos());
+
+        Ok(prpsd_board)
     }
 
     // pub fn assess_vacated(&mut self, from: Square, new_pid: String, updates: & mut Vec<(Square, Direction, Option<String>)>) {
-    pub fn assess_vacated(&mut self, from_piece: Piece, new_pid: String, updates: & mut Vec<(Square, Direction, Option<String>)>) {
+    pub fn assess_vacated(&mut self, from_piece: Piece, new_pid: String, updates: & mut Vec<(Square, Direction, Option<String>)>) -> Result<()> {
         // let mvng_piece = self.pieces.get(&from)
         //         .expect("assess_vacated: expected a piece to exist at the 'from' square");
-        let from_data = from_piece.get_piece_data();
+        let from_data = from_piece.get_piece_data()?;
         let pchar = from_piece.get_piece_type_as_char();
         let from = from_piece.get_square();
-        let to = Square::from_str(&new_pid[0..=1]).unwrap();
-        let mdir = get_direction(from, to)
-                .expect("assess_vacated: surely a legal move must have a valid direction?");
-        println!("Direction of {from}-{to} is {mdir:?}");
+        let to = Pid::new(&new_pid)?.square();
+        let mdir = get_direction(from, to).ok_or_else(|| Error::IllegalMove {
+            from,
+            to,
+            reason: "no compass direction joins the two squares".to_string(),
+        })?;
+        trace!("Direction of {from}-{to} is {mdir:?}");
         let _pid = format!("{}{}", to, pchar);
         let mut transfer_exchangers 
                 = | opp_dir: Direction, xrs: &String, opp_xrs: &String |
-                -> Vec<(Square, Direction, Option<String>)> {
+                -> Result<Vec<(Square, Direction, Option<String>)>> {
 
-            let pattern_len = 3;
             let dir = opp_dir.opposite();
             let mut dir_xrs: Option<String> = None;
             let mut  updts: Vec<(Square, Direction, Option<String>)> = Vec::new();
-            for (i, _char_chunk) in xrs.char_indices().step_by(pattern_len) {
-                if let Some(chunk) = xrs.get(i..(i + pattern_len)) {
+            let (_, chain_pids) = split_chain(xrs)?;
+            for chunk in chain_pids {
+                {
                     // println!("d chunk: {}", chunk);
-                    let od_sq = Square::from_str(&chunk[0..=1]).unwrap();
-                    let od_piece = self
-                        .pieces
-                        .get_mut(&od_sq)
-                        .expect("Expected a piece to exist at the given square");
+                    let od_sq = chunk.square();
+                    let od_piece = self.pieces.get_mut(&od_sq).ok_or_else(|| {
+                        Error::InconsistentExchangers(format!("exchanger {chunk} refers to empty square {od_sq}"))
+                    })?;
                     dir_xrs = od_piece.exchangers.get(&dir).cloned();
-                    let od_piece_data = od_piece.get_piece_data();
+                    let od_piece_data = od_piece.get_piece_data()?;
                     if od_piece_data.is_sliding == false {
                         // only possible for first exchanger in list - do not continue with list
                         updts.extend([(od_sq, opp_dir, Some(opp_xrs.clone()))]);
@@ -564,7 +931,7 @@ impl Board {
                             Some(exstng_xrs.to_string() + &opp_xrs.clone()),
                         )]);                       
                     } else {
-                        println!("ESCHEWING..ESCHEWING tramsfer_exchangers to piece of {od_sq} | {opp_dir}: {opp_xrs}");
+                        trace!("eschewing transfer_exchangers to piece of {od_sq} | {opp_dir}: {opp_xrs}");
                         // updts.extend([(od_sq, opp_dir, Some(opp_xrs.clone()))]);
                     }
                 }
@@ -572,8 +939,10 @@ impl Board {
             
             // Handle hanger-on after the loop (when mutable borrow is dropped)
             if let Some(d_pid) = dir_xrs {
-                let d_sq = Square::from_str(&d_pid[0..=1]).unwrap();
-                let d_piece = self.pieces.get(&d_sq).expect("Expected hanger-on piece to exist at the given square");
+                let d_sq = chain_head(&d_pid)?;
+                let d_piece = self.pieces.get(&d_sq).ok_or_else(|| {
+                    Error::InconsistentExchangers(format!("hanger-on {d_pid} refers to empty square {d_sq}"))
+                })?;
                 let exstng_xrs = d_piece.exchangers.get(&opp_dir).cloned();
                 if let Some(exstng_xrs) = exstng_xrs {
                     // println!("transfer_exchanger - the hanger-on {dir}: {d_pid}");
@@ -585,11 +954,11 @@ impl Board {
                 }                        
             }
 
-            updts
+            Ok(updts)
         };
 
         let undo_exchangers = | d: Direction |
-                    -> Vec<(Square, Direction, Option<String>)> {
+                    -> Result<Vec<(Square, Direction, Option<String>)>> {
 
             let od = d.opposite();
             let mut updts: Vec<(Square, Direction, Option<String>)> = Vec::new();
@@ -607,78 +976,107 @@ impl Board {
                     ulmt = 2;
                 }
     
-                let sq = &d_xr[llmt..ulmt];
-                let d_square = Square::from_str(sq).unwrap();
+                let d_square = ray_square(&d_xr[llmt..ulmt])?;
                 if (from_data.is_sliding || !sliding_rqrd)
                     && !(from_data.basic_piece_type == BasicPieceType::Pawn && VERTICALS.contains(&od)) {
                         updts.extend([(d_square, od, None)]);
                 }
             }
 
-            updts
+            Ok(updts)
         };
 
 
+        // a marked chain on a king is a pin line through the square, not pieces
+        // bearing on it, so it has nothing to hand on
+        let exchangers_towards = |drctn: &Direction| {
+            from_piece.exchangers.get(drctn).filter(|chain| !chain.starts_with(['<', '>']))
+        };
         for (d, od) in DIRECTION_PAIRS.iter() { 
-            let d_xrs_opt = from_piece.exchangers.get(d);
-            let od_xrs_opt = from_piece.exchangers.get(od);
-            if d_xrs_opt.is_some() && od_xrs_opt.is_some() {
-                let d_xrs = d_xrs_opt.unwrap();
-                let od_xrs = od_xrs_opt.unwrap();
-                if HALF_WINDS.contains(d) {
-                    let d_sq = Square::from_str(&d_xrs[0..=1]).unwrap();
-                    let od_sq = Square::from_str(&od_xrs[0..=1]).unwrap();
-                    updates.extend([(d_sq, *d, None)]);
-                    updates.extend([(od_sq, *od, None)]);
-                } else  {
-                    let od_trnsfr_updates = transfer_exchangers(*od, d_xrs, od_xrs);
-                    let d_trnsfr_updates = transfer_exchangers(*d, od_xrs, d_xrs);
-                    updates.extend(od_trnsfr_updates);
-                    updates.extend(d_trnsfr_updates);
-                }
-                println!("assess_vacated we got some d_xrs and some od_xrs");
-            } else if d_xrs_opt.is_some() {
-                let d_xrs = d_xrs_opt.unwrap();
-                let d_sq = Square::from_str(&d_xrs[0..=1]).unwrap();
-
-                if HALF_WINDS.contains(d) {
-                    updates.extend([(d_sq, *od, None)]);
-                } else  {
-                    updates.extend([(d_sq, *od, None)]);
+            match (exchangers_towards(d), exchangers_towards(od)) {
+                (Some(d_xrs), Some(od_xrs)) => {
+                    if HALF_WINDS.contains(d) {
+                        updates.extend([(chain_head(d_xrs)?, *d, None)]);
+                        updates.extend([(chain_head(od_xrs)?, *od, None)]);
+                    } else  {
+                        let od_trnsfr_updates = transfer_exchangers(*od, d_xrs, od_xrs)?;
+                        let d_trnsfr_updates = transfer_exchangers(*d, od_xrs, d_xrs)?;
+                        updates.extend(od_trnsfr_updates);
+                        updates.extend(d_trnsfr_updates);
+                    }
+                    trace!("assess_vacated we got some d_xrs and some od_xrs");
                 }
-                println!("assess_vacated we got some d_xrs but none of the other");
-            } else if od_xrs_opt.is_some() {
-                let od_xrs = od_xrs_opt.unwrap();
-                let od_sq = Square::from_str(&od_xrs[0..=1]).unwrap();
-
-                if HALF_WINDS.contains(od) {
-                    updates.extend([(od_sq, *d, None)]);
-                } else  {
-                    updates.extend([(od_sq, *d, None)]);
+                (Some(d_xrs), None) => {
+                    updates.extend([(chain_head(d_xrs)?, *od, None)]);
+                    // the piece beyond on the other side may still hold the mover
+                    if from_data.directions.contains(od) {
+                        updates.extend(undo_exchangers(*od)?);
+                    }
+                    trace!("assess_vacated we got some d_xrs but none of the other");
                 }
-                println!("assess_vacated we got some od_xrs but none of the other");
-            } else {
-
-                if from_data.directions.contains(d) {
-                    let d_updates = undo_exchangers(*d);
-                    updates.extend(d_updates);
+                (None, Some(od_xrs)) => {
+                    updates.extend([(chain_head(od_xrs)?, *d, None)]);
+                    if from_data.directions.contains(d) {
+                        updates.extend(undo_exchangers(*d)?);
+                    }
+                    trace!("assess_vacated we got some od_xrs but none of the other");
                 }
-
-                if from_data.directions.contains(od) {
-                    let od_updates = undo_exchangers(*od);
-                    updates.extend(od_updates);
+                (None, None) => {
+                    if from_data.directions.contains(d) {
+                        updates.extend(undo_exchangers(*d)?);
+                    }
+                    if from_data.directions.contains(od) {
+                        updates.extend(undo_exchangers(*od)?);
+                    }
                 }
             }
         }
+        Ok(())
     }
 
-    pub fn assess_landed(&mut self, landed_pid: String, drctn_back: Direction, updates: & mut Vec<(Square, Direction, Option<String>)>)  {
+    /// Cuts a captured piece, and whatever stood behind it, out of the chains still
+    /// naming it; `assess_landed` has already put in the piece that took it.
+    fn forget_captured(&mut self, captured: &str) {
+        for piece in self.pieces.values_mut() {
+            piece.exchangers.retain(|_, chain| {
+                let start = usize::from(chain.starts_with(['<', '>']));
+                match (start..chain.len()).step_by(3).find(|&i| chain.get(i..i + 3) == Some(captured)) {
+                    Some(i) => {
+                        chain.truncate(i);
+                        chain.len() > start
+                    }
+                    None => true,
+                }
+            });
+        }
+    }
 
-        let landed_square = Square::from_str(&landed_pid[0..=1]).unwrap();
-        let landed_piece = Piece::new(Pid::new(&landed_pid).unwrap());
-        let landed_piece_data = landed_piece.get_piece_data();
+    pub fn assess_landed(&mut self, landed_pid: String, drctn_back: Direction, updates: & mut Vec<(Square, Direction, Option<String>)>) -> Result<()> {
+
+        let landed_piece = Piece::new(Pid::new(&landed_pid)?);
+        let landed_square = landed_piece.get_square();
+        let landed_piece_data = landed_piece.get_piece_data()?;
+        // the chain the landed piece has towards `drctn`: the first piece that way,
+        // if it bears back, and the pieces backing that one up
+        let backing = | drctn: Direction | -> Result<String> {
+            let Some(b_square) = first_blocker(landed_square, drctn, self.occupied) else { return Ok(String::new()) };
+            let b_piece = self.pieces.get(&b_square).ok_or_else(|| {
+                Error::InconsistentExchangers(format!("occupied bitboard has {b_square} set but no piece there"))
+            })?;
+            let b_data = b_piece.get_piece_data()?;
+            let back = drctn.opposite();
+            let adjacent = landed_square.offset(drctn) == Some(b_square);
+            if !b_data.directions.contains(&back)
+                    || !(b_data.is_sliding || adjacent)
+                    || (b_data.basic_piece_type == BasicPieceType::Pawn && VERTICALS.contains(&back)) {
+                return Ok(String::new());
+            }
+            let onward = b_piece.exchangers.get(&drctn)
+                .filter(|chain| !HALF_WINDS.contains(&drctn) && !chain.starts_with(['<', '>']));
+            Ok(format!("{}{}", b_piece.get_pid(), onward.map_or("", String::as_str)))
+        };
         let impose 
-                = | drctn: Direction | -> Vec<(Square, Direction, Option<String>)> {
+                = | drctn: Direction | -> Result<Vec<(Square, Direction, Option<String>)>> {
 
             let opp_drctn = drctn.opposite();
             let mut xr_updates: Vec<(Square, Direction, Option<String>)> = Vec::new();
@@ -696,155 +1094,164 @@ impl Board {
                     ulmt = 2;
                 }
 
-                let sq = &ray[llmt..ulmt];
-                let d_square = Square::from_str(sq).unwrap();
-                let d_piece = self.pieces.get(&d_square).unwrap(); // panic if no piece on square!
+                let d_square = ray_square(&ray[llmt..ulmt])?;
+                let d_piece = self.pieces.get(&d_square).ok_or_else(|| {
+                    Error::InconsistentExchangers(format!("occupied bitboard has {d_square} set but no piece there"))
+                })?;
                 let d_piece_pid = d_piece.get_pid().to_string();
-                let d_piece_data = d_piece.get_piece_data();
-                if d_piece_data.directions.contains(&opp_drctn) { // don't forget pawn anomolies
-                    if (d_piece_data.is_sliding || !sliding_rqrd)
-                            && !(d_piece_data.basic_piece_type == BasicPieceType::Pawn && VERTICALS.contains(&opp_drctn)) {
-                        match d_piece.exchangers.get(&drctn) {
-                            Some(onward_d_xrs) => {
-                                xr_updates.push((landed_square, drctn, Some(d_piece_pid + onward_d_xrs)));                                
-                            },
-                            None => {
-                                if d_piece_data.basic_piece_type == BasicPieceType::King && !HALF_WINDS.contains(&drctn)  {
-                                    let ray = generate_ray_path(d_square, opp_drctn, self.occupied).unwrap();
-
-                                    // ??????
-
-                                    let pin_seq = Board::extract_pin_seq(self,d_piece_data, &ray, opp_drctn);
-                                    if pin_seq.is_some() {
-                                        let pin = pin_seq.unwrap();
-                                        xr_updates.push((d_square, opp_drctn, Some(pin)));
-                                        // print!("Just detected a King pin {pin}")
-                                    }
-                                } //else {
-                                    xr_updates.push((landed_square, drctn, Some(d_piece_pid.clone())));
-                                //}
-                            }
-                        }
-                    } else if landed_piece_data.directions.contains(&drctn) {
-                        if landed_piece_data.is_sliding || !sliding_rqrd {
-                            xr_updates.push((d_square, opp_drctn, Some(landed_pid.clone())));
+                let d_piece_data = d_piece.get_piece_data()?;
+                let bears_back = d_piece_data.directions.contains(&opp_drctn) // don't forget pawn anomolies
+                    && (d_piece_data.is_sliding || !sliding_rqrd)
+                    && !(d_piece_data.basic_piece_type == BasicPieceType::Pawn && VERTICALS.contains(&opp_drctn));
+                if bears_back {
+                    match d_piece.exchangers.get(&drctn) {
+                        Some(onward_d_xrs) => {
+                            xr_updates.push((landed_square, drctn, Some(d_piece_pid + onward_d_xrs)));                                
+                        },
+                        None => {
+                            if d_piece_data.basic_piece_type == BasicPieceType::King && !HALF_WINDS.contains(&drctn)  {
+                                // the landed piece itself is on this ray, so it cannot be empty
+                                let ray = generate_ray_path(d_square, opp_drctn, self.occupied).unwrap_or_default();
+
+                                // ??????
+
+                                if let Some(pin) = Board::extract_pin_seq(self,d_piece_data, &ray, opp_drctn) {
+                                    xr_updates.push((d_square, opp_drctn, Some(pin)));
+                                    // print!("Just detected a King pin {pin}")
+                                }
+                            } //else {
+                                xr_updates.push((landed_square, drctn, Some(d_piece_pid.clone())));
+                            //}
                         }
-                    } else {
-                        println!("Not sure of why this situation should be processed!")
                     }
-
-
+                }
+                let reaches = landed_piece_data.directions.contains(&drctn)
+                    && (landed_piece_data.is_sliding || !sliding_rqrd)
+                    && !(landed_piece_data.basic_piece_type == BasicPieceType::Pawn && VERTICALS.contains(&drctn));
+                if reaches {
+                    // whether or not the piece ahead looks back, the landed piece and
+                    // whatever backs it up now bear on it
+                    xr_updates.push((d_square, opp_drctn, Some(landed_pid.clone() + &backing(opp_drctn)?)));
+                } else if !bears_back {
+                    trace!("impose {drctn}: {d_square} neither joins nor is reached by {landed_pid}");
                 }
             } else {
-                print!("no ray found in 'impose'");
+                trace!("no ray found in 'impose' {drctn} of {landed_square}");
             }
-            xr_updates
+            Ok(xr_updates)
         };        
 
         for (d, od) in DIRECTION_PAIRS.iter() { 
-            let d_updates = impose(*d);
+            let d_updates = impose(*d)?;
             updates.extend(d_updates);
     
-            let od_updates = impose(*od);
+            let od_updates = impose(*od)?;
             updates.extend(od_updates);
         }
+        Ok(())
+    }
+
+    /// The pieces of the side that just moved which now attack the opposing king.
+    /// Only a piece that `landed` can give a direct check (the castling rook counts);
+    /// any other checker must sit behind one of the `vacated` squares on a line to the king.
+    fn find_checkers(&self, landed: &[Square], vacated: &[Square]) -> (Vec<Pid>, Option<CheckKind>) {
+        let Some(king) = self.king_location(self.turn.opposite()) else { return (Vec::new(), None) };
+        let king_bit = 1u64 << king.index();
+        let gives_check = |square: Square| {
+            self.pieces.get(&square).is_some_and(|piece| {
+                piece.get_side() == self.turn
+                    && piece.get_piece_data().is_ok_and(|data| piece_attacks(data, square, self.occupied) & king_bit != 0)
+            })
+        };
+
+        let mut checks: Vec<Pid> = Vec::new();
+        let mut direct = false;
+        for square in landed {
+            if gives_check(*square) {
+                checks.push(self.pieces[square].pid.clone());
+                direct = true;
+            }
+        }
+        for square in vacated {
+            let Some(drctn) = get_direction(king, *square).filter(|d| !d.is_half_wind()) else { continue };
+            if let Some(slider) = first_blocker(king, drctn, self.occupied)
+                && !landed.contains(&slider)
+                && gives_check(slider)
+                && !checks.iter().any(|pid| pid.square() == slider)
+            {
+                checks.push(self.pieces[&slider].pid.clone());
+            }
+        }
+
+        let check_kind = match (checks.len(), direct) {
+            (0, _) => None,
+            (1, true) => Some(CheckKind::Direct),
+            (1, false) => Some(CheckKind::Discovered),
+            _ => Some(CheckKind::Double),
+        };
+        (checks, check_kind)
+    }
+
+    /// Call before `update_status` flips the turn. `resets` is true for pawn moves and captures.
+    fn advance_clocks(&mut self, resets: bool) {
+        self.halfmove_clock = if resets { 0 } else { self.halfmove_clock + 1 };
+        if self.turn == Side::Black {
+            self.fullmove_number += 1;
+        }
     }
 
     pub fn update_status(&mut self, from: Square, to: Square, piece_type: char){
         // the current player who's turn it is has not finished the move yet...
         // here is the final accounting!
-        let mut turn_king;
-        if self.turn == Side::White {
-            turn_king = 'K';
-        } else {
-            turn_king = 'k';
+        // the squares a move empties can each open a line onto the opposing king: the
+        // origin square, and the victim's square after an en passant capture
+        let mut vacated = vec![from];
+        let victim = to.en_passant_victim();
+        if piece_type.eq_ignore_ascii_case(&'P') && from.file() != to.file() && !self.is_square_occupied(victim) {
+            vacated.push(victim);
         }
-
-        let mut opp_king;
-        if turn_king == 'K' {
-            opp_king = 'k';
-        } else {
-            opp_king = 'K';
+        let mut landed = vec![to];
+        if let Some((rook_from, rook_to)) = Board::castling_rook_squares(piece_type, from, to) {
+            vacated.push(rook_from);
+            landed.push(rook_to);
         }
-
-        // check for checks
-        let mut checks: Vec<Pid> = Vec::new();
-        for (_sq, piece) in self.pieces.iter() {
-            let piece_type_char = piece.get_piece_type_as_char();
-            if piece_type_char == opp_king { // we search for the opposite side's king
-                let piece_side = piece.get_side();
-                let exchangers_clone = piece.exchangers.clone();
-                for (_d, xrs) in exchangers_clone {
-                    let xr = &xrs[0..3]; // take first three characters
-                    if xr.chars().all(char::is_alphanumeric) { // Check if all characters are alphabetic i.e. don't include '<' or '>'
-                        let xr_pid = Pid::new(xr).unwrap();
-                        let xr_pid_for_check = xr_pid.clone(); // Clone xr_pid before it's potentially moved
-                        let xr_side = xr_pid.get_side(); // This line likely moves `xr_pid`
-                        if xr_side != piece_side {
-                            checks.push(xr_pid_for_check); // Use the cloned value
-                        }
-                    }
-                }
+        let (checks, check_kind) = self.find_checkers(&landed, &vacated);
+        self.checks = checks;
+        self.check_kind = check_kind;
+
+        // moving the king or a rook, or capturing a rook at home, gives up castling
+        for square in [from, to] {
+            match square {
+                Square::e1 => (self.castling.white_king_side, self.castling.white_queen_side) = (false, false),
+                Square::h1 => self.castling.white_king_side = false,
+                Square::a1 => self.castling.white_queen_side = false,
+                Square::e8 => (self.castling.black_king_side, self.castling.black_queen_side) = (false, false),
+                Square::h8 => self.castling.black_king_side = false,
+                Square::a8 => self.castling.black_queen_side = false,
+                _ => {}
             }
         }
-        if checks.len() > 0 {
-            self.checks.extend(checks);
-        } else {
-            self.checks.clear();
-        }
 
         let set_en_passant = || -> Option<Square> {
             // ep_capture_sqid = file: wsqid.file, rank: (wsqid_rank == 5) ? 6 : 3 
-            let sqid_file = to.to_string().chars().nth(0).unwrap();
-            let sqid_rank = to.to_string().chars().nth(1).unwrap();
-            let new_rank;
-            if sqid_rank == '5' {
-                new_rank = '6';
-            } else {
-                new_rank = '3';
-            }
-            let ep_capture_sqid = format!("{}{}", sqid_file, new_rank);
-            let ep_capture_square = Square::from_str(&ep_capture_sqid).expect("Failed to parse Square from string");
-            println!("set_en_passant: {}{} - epcapturesqid: {}, epcapturesquare: {}",
-                        sqid_file, new_rank, ep_capture_sqid, ep_capture_square);
+            let new_rank = if to.rank() == Rank::R5 { Rank::R6 } else { Rank::R3 };
+            let ep_capture_square = Square::from_file_rank(to.file(), new_rank);
+            trace!("set_en_passant: epcapturesquare: {}", ep_capture_square);
             Some(ep_capture_square)
         };
 
+        // an en passant chance lasts a single move
+        self.capture_square_en_passant = None;
+
         // check for en-passant
-        if 'P' == piece_type.to_ascii_uppercase() {
-            let from_rank = from.to_string().chars().nth(1).unwrap();
-            let to_rank = to.to_string().chars().nth(1).unwrap();
-            let to_file = to.to_string().chars().nth(0).unwrap();
-
-            // Cast both characters to u32 and find the absolute difference.
-            if u32::abs_diff(from_rank as u32, to_rank as u32) == 2 {
-                let wsqid;
-                if to_file != 'a' {
-                    wsqid = get_next_sqid(to, Direction::W).unwrap();
-                    let wsquare = Square::from_str(&wsqid).unwrap();
-                    let wpiece_opt = self.pieces.get(&wsquare);
-                    if wpiece_opt.is_some() {
-                        let wpiece = wpiece_opt.unwrap();
-                        if wpiece.get_piece_type_as_char().to_ascii_uppercase() == 'P'
-                                && wpiece.get_side() != self.turn {
-                            // self.capture_square_en_passant = set_en_passant(wsqid);
-                            self.capture_square_en_passant = set_en_passant();
-                        }
-                    }
-                }
-                let esqid;
-                if to_file != 'h' {
-                    esqid = get_next_sqid(to, Direction::E).unwrap();
-                    let esquare = Square::from_str(&esqid).unwrap();
-                    let epiece_opt = self.pieces.get(&esquare);
-                    if epiece_opt.is_some() {
-                        let epiece = epiece_opt.unwrap();
-                        if epiece.get_piece_type_as_char().to_ascii_uppercase() == 'P'
-                                && epiece.get_side() != self.turn {
-                            // self.capture_square_en_passant = set_en_passant(esqid);
-                            self.capture_square_en_passant = set_en_passant();
-                        }
-                    }
+        if piece_type.eq_ignore_ascii_case(&'P') && from.rank().index().abs_diff(to.rank().index()) == 2 {
+            for side_drctn in HORIZONTALS.iter() {
+                if let Some(side_square) = to.offset(*side_drctn)
+                    && let Some(side_piece) = self.pieces.get(&side_square)
+                    && side_piece.get_piece_type_as_char().eq_ignore_ascii_case(&'P')
+                    && side_piece.get_side() != self.turn
+                {
+                    self.capture_square_en_passant = set_en_passant();
                 }
             }
         }
@@ -857,7 +1264,87 @@ impl Board {
     }
 
     pub fn update_xchngr(&self, xchngr: String, drctn: Direction, val: Option<String>) {
-        println!("xchngr: {xchngr}, drctn: {drctn}, val: {val:?}")
+        debug!("xchngr: {xchngr}, drctn: {drctn}, val: {val:?}")
+    }
+
+    pub fn processing_stats(&self) -> &ProcessingStats {
+        &self.stats
+    }
+
+    pub fn turn(&self) -> Side {
+        self.turn
+    }
+
+    pub fn set_turn(&mut self, side: Side) {
+        self.turn = side;
+    }
+
+    pub fn en_passant_square(&self) -> Option<Square> {
+        self.capture_square_en_passant
+    }
+
+    pub fn set_en_passant_square(&mut self, square: Option<Square>) {
+        self.capture_square_en_passant = square;
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling
+    }
+
+    pub fn set_castling_rights(&mut self, rights: CastlingRights) {
+        self.castling = rights;
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    pub fn set_move_clocks(&mut self, halfmove_clock: u32, fullmove_number: u32) {
+        self.halfmove_clock = halfmove_clock;
+        self.fullmove_number = fullmove_number;
+    }
+
+    /// Every move played on this board, promotions included.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn checks(&self) -> &[Pid] {
+        &self.checks
+    }
+
+    pub fn check_kind(&self) -> Option<CheckKind> {
+        self.check_kind
+    }
+
+    /// The pin lines recorded in the kings' exchangers, as (king square, direction,
+    /// chain). A chain starting with '<' runs through a piece of the king's colour
+    /// (a pin), one starting with '>' through an enemy piece.
+    pub fn pin_lines(&self) -> Vec<(Square, Direction, &str)> {
+        let mut lines = Vec::new();
+        for king in [self.white_king_location, self.black_king_location].into_iter().flatten() {
+            if let Some(piece) = self.pieces.get(&king) {
+                for drctn in Direction::iter() {
+                    if let Some(chain) = piece.exchangers.get(&drctn)
+                        && (chain.starts_with('<') || chain.starts_with('>'))
+                    {
+                        lines.push((king, drctn, chain.as_str()));
+                    }
+                }
+            }
+        }
+        lines
+    }
+
+    pub fn king_location(&self, side: Side) -> Option<Square> {
+        match side {
+            Side::White => self.white_king_location,
+            Side::Black => self.black_king_location,
+        }
     }
 
     pub fn get_piece_on(&self, square: Square) -> Option<&Piece> {
@@ -867,7 +1354,8 @@ impl Board {
 
     pub fn remove_piece_from(&mut self, sq: Square) {
         // let square = Square::from_str(sq).unwrap();
-        if let Some(_piece) = self.pieces.remove(&sq) {
+        if let Some(piece) = self.pieces.remove(&sq) {
+            self.forget_king(&piece);
             let bit = square_to_bit(sq);
             self.occupied &= !(1u64 << bit);
         }
@@ -885,6 +1373,8 @@ impl Board {
     pub fn clear(&mut self) {
         self.pieces.clear();
         self.occupied = 0;
+        self.white_king_location = None;
+        self.black_king_location = None;
     }
 
     pub fn len(&self) -> usize {
@@ -895,97 +1385,222 @@ impl Board {
         self.pieces.is_empty()
     }
 
-    pub fn init_from_pid_list(&mut self, pids: Vec<&str>) {
+    pub fn init_from_pid_list(&mut self, pids: Vec<&str>) -> Result<()> {
         self.occupied = 0;
         for pid in pids {
-            self.create_and_place_piece(&pid.to_string());
+            self.create_and_place_piece(pid)?;
+        }
+        Ok(())
+    }
+
+    /// Builds a board from user supplied pids, rejecting positions that could not
+    /// arise in a game. Unlike `init_from_pid_list`, the exchangers are built too;
+    /// the checks are `BoardBuilder::build`'s.
+    pub fn from_pid_list(pids: Vec<&str>, turn: Side) -> Result<Board> {
+        let mut builder = Board::builder().side_to_move(turn);
+        for pid in pids {
+            let pid = Pid::new(pid)?;
+            let piece_type = PieceType::from_char(pid.piece_char()).ok_or_else(|| Error::InvalidPid(pid.to_string()))?;
+            builder = builder.piece(pid.square(), piece_type);
         }
+        builder.build()
+    }
+
+    /// Reports every reason the position could not arise in a game, see `legality`.
+    pub fn check_legality(&self) -> Result<()> {
+        let violations = check_position(self);
+        if violations.is_empty() { Ok(()) } else { Err(Error::IllegalPosition(violations)) }
     }
         
-    pub fn init_double_discovered_check(&mut self) {
-        self.occupied = 0;
-        self.create_and_place_piece("e1K");
-        self.create_and_place_piece("a6k");
-        self.create_and_place_piece("a5P");
-        self.create_and_place_piece("b7p");
-        self.create_and_place_piece("a1R");
-        self.create_and_place_piece("g2B");
-        self.create_and_place_piece("g1B");
-        self.create_and_place_piece("d8N");
+    pub fn init_standard(&mut self) -> Result<()> {
+        // White pieces
+        self.create_and_place_piece("a1R")?;
+        self.create_and_place_piece("b1N")?;
+        self.create_and_place_piece("c1B")?;
+        self.create_and_place_piece("d1Q")?;
+        self.create_and_place_piece("e1K")?;
+        self.create_and_place_piece("f1B")?;
+        self.create_and_place_piece("g1N")?;
+        self.create_and_place_piece("h1R")?;
+        self.create_and_place_piece("a2P")?;
+        self.create_and_place_piece("b2P")?;
+        self.create_and_place_piece("c2P")?;
+        self.create_and_place_piece("d2P")?;
+        self.create_and_place_piece("e2P")?;
+        self.create_and_place_piece("f2P")?;
+        self.create_and_place_piece("g2P")?;
+        self.create_and_place_piece("h2P")?;
+
+        // Black pieces
+        self.create_and_place_piece("a8r")?;
+        self.create_and_place_piece("b8n")?;
+        self.create_and_place_piece("c8b")?;
+        self.create_and_place_piece("d8q")?;
+        self.create_and_place_piece("e8k")?;
+        self.create_and_place_piece("f8b")?;
+        self.create_and_place_piece("g8n")?;
+        self.create_and_place_piece("h8r")?;
+        self.create_and_place_piece("a7p")?;
+        self.create_and_place_piece("b7p")?;
+        self.create_and_place_piece("c7p")?;
+        self.create_and_place_piece("d7p")?;
+        self.create_and_place_piece("e7p")?;
+        self.create_and_place_piece("f7p")?;
+        self.create_and_place_piece("g7p")?;
+        self.create_and_place_piece("h7p")?;
+        Ok(())
     }
 
-    pub fn init_custom_from(&mut self) {
-        self.occupied = 0;
-        self.create_and_place_piece("e1K");
-        self.create_and_place_piece("e2Q");
-        self.create_and_place_piece("e3R");
-        self.create_and_place_piece("e4R");
+    /// Checks the board's internal invariants and reports every violation found: the
+    /// structural ones of `validate_structure`, then the exchangers and the attack map
+    /// against a fresh `build_all_xchngrs`.
+    pub fn validate(&self) -> Result<()> {
+        let mut violations = self.structural_violations();
+        let mut rebuilt = self.clone();
+        rebuilt.build_all_xchngrs()?;
+        for (square, piece) in &self.pieces {
+            let Some(fresh) = rebuilt.pieces.get(square) else { continue };
+            for drctn in Direction::iter() {
+                let held = piece.exchangers.get(&drctn);
+                let expected = fresh.exchangers.get(&drctn);
+                if held != expected {
+                    violations.push(format!(
+                        "{} {drctn} expected {} got {}",
+                        piece.pid,
+                        expected.map_or("-", String::as_str),
+                        held.map_or("-", String::as_str)
+                    ));
+                }
+            }
+        }
+        if let (Some(held_map), Some(rebuilt_map)) = (self.attack_map(), rebuilt.attack_map()) {
+            for index in 0..64 {
+                let square = Square::from_index(index).expect("64 squares");
+                for side in [Side::White, Side::Black] {
+                    let (held, expected) = (held_map.attackers(square, side), rebuilt_map.attackers(square, side));
+                    if held != expected {
+                        violations.push(format!("{square} {side:?} attackers expected {expected:#018x} got {held:#018x}"));
+                    }
+                    let (held, expected) = (held_map.xray_attackers(square, side), rebuilt_map.xray_attackers(square, side));
+                    if held != expected {
+                        violations.push(format!("{square} {side:?} x-ray attackers expected {expected:#018x} got {held:#018x}"));
+                    }
+                }
+            }
+        }
 
-        self.create_and_place_piece("b2B");
-        self.create_and_place_piece("c6N");
-        self.create_and_place_piece("e5P");
-        self.create_and_place_piece("g4n");
-        self.create_and_place_piece("g7b");
+        if violations.is_empty() { Ok(()) } else { Err(Error::CorruptBoard(violations)) }
+    }
 
-        self.create_and_place_piece("e6r");
-        self.create_and_place_piece("e7r");
-        self.create_and_place_piece("e8q");
-        self.create_and_place_piece("f8k");
-        self.create_and_place_piece("f6p");
+    /// The checks of `validate` that need no rebuild: the occupied bitboard against the
+    /// pieces map, each pid against its map key, the recorded king locations, every
+    /// exchanger chain against the pieces it names, and that there is an attack map.
+    pub fn validate_structure(&self) -> Result<()> {
+        let violations = self.structural_violations();
+        if violations.is_empty() { Ok(()) } else { Err(Error::CorruptBoard(violations)) }
     }
 
+    fn structural_violations(&self) -> Vec<String> {
+        let mut violations: Vec<String> = Vec::new();
+
+        let mut from_pieces = 0u64;
+        for (square, piece) in &self.pieces {
+            from_pieces |= 1u64 << square.index();
+            if piece.get_square() != *square {
+                violations.push(format!("piece {} is stored under {square}", piece.pid));
+            }
+        }
+        if from_pieces != self.occupied {
+            violations.push(format!(
+                "occupied bitboard {:#018x} does not match the pieces map {:#018x}",
+                self.occupied, from_pieces
+            ));
+        }
+
+        for (location, king_char) in [(self.white_king_location, 'K'), (self.black_king_location, 'k')] {
+            match location {
+                Some(square) => match self.pieces.get(&square) {
+                    Some(piece) if piece.get_piece_type_as_char() == king_char => {}
+                    _ => violations.push(format!("king '{king_char}' recorded on {square} but not found there")),
+                },
+                None => {
+                    if let Some(piece) = self.pieces.values().find(|p| p.get_piece_type_as_char() == king_char) {
+                        violations.push(format!("king {} is on the board but its location is not recorded", piece.pid));
+                    }
+                }
+            }
+        }
 
+        for (square, piece) in &self.pieces {
+            for (drctn, xrs) in &piece.exchangers {
+                self.validate_chain(piece, *square, *drctn, xrs, &mut violations);
+            }
+        }
 
-    pub fn init_custom1(&mut self) {
-        self.create_and_place_piece("f7R");
-        self.create_and_place_piece("e6p");
-        self.create_and_place_piece("a5r");
-        self.create_and_place_piece("b5B");
-        self.create_and_place_piece("d5K");
-        self.create_and_place_piece("d4P");
-        self.create_and_place_piece("d3Q");
-        self.create_and_place_piece("f3b");
-        self.create_and_place_piece("c2n");
-        self.create_and_place_piece("g2B");
-        self.create_and_place_piece("a1k");
+        if self.attack_map.is_none() {
+            violations.push("no attack map".to_string());
+        }
+        violations
     }
 
-    pub fn init_standard(&mut self) {
-        // White pieces
-        self.create_and_place_piece("a1R");
-        self.create_and_place_piece("b1N");
-        self.create_and_place_piece("c1B");
-        self.create_and_place_piece("d1Q");
-        self.create_and_place_piece("e1K");
-        self.create_and_place_piece("f1B");
-        self.create_and_place_piece("g1N");
-        self.create_and_place_piece("h1R");
-        self.create_and_place_piece("a2P");
-        self.create_and_place_piece("b2P");
-        self.create_and_place_piece("c2P");
-        self.create_and_place_piece("d2P");
-        self.create_and_place_piece("e2P");
-        self.create_and_place_piece("f2P");
-        self.create_and_place_piece("g2P");
-        self.create_and_place_piece("h2P");
+    // An exchanger chain is an optional pin marker ('<' or '>') followed by 3-char pids,
+    // each of which must be on the board, on the ray, and further out than the last.
+    fn validate_chain(&self, piece: &Piece, square: Square, drctn: Direction, xrs: &str, violations: &mut Vec<String>) {
+        let chain = xrs.trim_start_matches(['<', '>']);
+        if chain.is_empty() || !chain.len().is_multiple_of(3) {
+            violations.push(format!("{} {drctn}: malformed exchanger string '{xrs}'", piece.pid));
+            return;
+        }
+        let mut last_distance = 0;
+        for i in (0..chain.len()).step_by(3) {
+            let Ok(xr_pid) = Pid::new(&chain[i..i + 3]) else {
+                violations.push(format!("{} {drctn}: '{}' is not a pid", piece.pid, &chain[i..i + 3]));
+                continue;
+            };
+            let xr_square = xr_pid.square();
+            match self.pieces.get(&xr_square) {
+                Some(xr_piece) if xr_piece.pid == xr_pid => {}
+                _ => violations.push(format!("{} {drctn}: {xr_pid} is not on {xr_square}", piece.pid)),
+            }
+            let on_ray = if drctn.is_half_wind() {
+                square.offset(drctn) == Some(xr_square)
+            } else {
+                get_direction(square, xr_square) == Some(drctn)
+            };
+            let distance = square.distance(xr_square);
+            if !on_ray || distance <= last_distance {
+                violations.push(format!("{} {drctn}: {xr_pid} is out of place on the ray", piece.pid));
+            }
+            last_distance = distance;
+        }
+    }
 
-        // Black pieces
-        self.create_and_place_piece("a8r");
-        self.create_and_place_piece("b8n");
-        self.create_and_place_piece("c8b");
-        self.create_and_place_piece("d8q");
-        self.create_and_place_piece("e8k");
-        self.create_and_place_piece("f8b");
-        self.create_and_place_piece("g8n");
-        self.create_and_place_piece("h8r");
-        self.create_and_place_piece("a7p");
-        self.create_and_place_piece("b7p");
-        self.create_and_place_piece("c7p");
-        self.create_and_place_piece("d7p");
-        self.create_and_place_piece("e7p");
-        self.create_and_place_piece("f7p");
-        self.create_and_place_piece("g7p");
-        self.create_and_place_piece("h7p");
+    // In debug builds every move is validated, so corruption surfaces at the move that
+    // caused it rather than several moves later. The full path has just rebuilt its
+    // exchangers, so comparing them against another rebuild would prove nothing: its
+    // `check` is `validate_structure`, the pre-processed path's the whole of `validate`.
+    fn debug_validate(&self, from: Square, to: Square, check: fn(&Board) -> Result<()>) -> Result<()> {
+        if !cfg!(debug_assertions) {
+            return Ok(());
+        }
+        match check(self) {
+            Err(Error::CorruptBoard(violations)) => Err(Error::CorruptBoard(
+                violations.into_iter().map(|v| format!("after {from}-{to}: {v}")).collect(),
+            )),
+            other => other,
+        }
+    }
+
+    /// `None` until the exchangers are built, and on boards stripped of them.
+    pub fn attack_map(&self) -> Option<&AttackMap> {
+        self.attack_map.as_deref()
+    }
+
+    fn update_attack_map(&mut self, changed: u64) {
+        if let Some(mut map) = self.attack_map.take() {
+            map.update(self, changed);
+            self.attack_map = Some(map);
+        }
     }
 
     pub fn get_occupied_bitboard(&self) -> u64 {
@@ -1020,172 +1635,251 @@ impl Board {
                 format!("_");
             }
         }
-        writeln!(&mut out, "occupied: {}, moves: {:?}, turn: {:?}, checks: {}, en_passant: {:?}",
-                    self.occupied, self.moves, self.turn, checks, self.capture_square_en_passant);
-        for (_square, piece) in &self.pieces {
+        let moves: Vec<String> = self.moves.iter().map(Move::to_uci).collect();
+        writeln!(&mut out, "occupied: {}, moves: [{}], turn: {:?}, checks: {}, en_passant: {:?}",
+                    self.occupied, moves.join(", "), self.turn, checks, self.capture_square_en_passant).unwrap();
+        for piece in self.pieces_in_square_order() {
+            writeln!(&mut out, "{}", piece).unwrap();
+        }
+        out
+    }
+
+    fn pieces_in_square_order(&self) -> Vec<&Piece> {
+        let mut pieces: Vec<&Piece> = self.pieces.values().collect();
+        pieces.sort_by_key(|piece| piece.get_square().index());
+        pieces
+    }
+
+    /// A dump that depends only on the position, never on hashing or history: the
+    /// FEN and checks, then each piece in square order with its exchangers in
+    /// direction order. Golden files are written in this form.
+    pub fn canonical_string(&self) -> String {
+        let mut out = String::new();
+        let checks: Vec<&str> = self.checks.iter().map(Pid::as_str).collect();
+        writeln!(&mut out, "fen: {}", self.to_fen()).unwrap();
+        match self.check_kind {
+            Some(kind) => writeln!(&mut out, "checks: {kind:?} [{}]", checks.join(",")).unwrap(),
+            None => writeln!(&mut out, "checks: none").unwrap(),
+        }
+        for piece in self.pieces_in_square_order() {
             writeln!(&mut out, "{}", piece).unwrap();
         }
         out
     }
 }
 
+/// An ASCII diagram; `to_ordered_string` has the exchanger dump.
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_ordered_string())
+        write!(f, "{}", self.diagram(&DiagramOptions::ascii()))
     }
 }
 
 
 
-pub fn move_framework(pids: Vec<&str>, moves: Vec<(Square, Square)>) {
-
-    println!("=== Using move_test_framework for position: {pids:?} and moves: {moves:?} ===");
-    
-    let mut board = Board::new();
-    board.init_from_pid_list(pids);
-    board.build_all_xchngrs();
-    println!("Original board:\n{board}");
-    
-    // let next_board = board.clone();
-    // let prpsd_board = board.clone();
-
-    for (from,  to) in moves {
-        let mut next_board = board.clone();
-        next_board = next_board
-            .full_process_move(from, to);
-
-        println!("Maximally processed exchangers post move:\n{}", next_board);
-        
-        let mut prpsd_board = board.clone();
-        prpsd_board = prpsd_board
-            .pre_processed_move(from, to);
-
-        println!("Pre-processed exchangers post move:\n{}", prpsd_board);
-    
-        let next_str = next_board.to_ordered_string();
-        let prpsd_str = prpsd_board.to_ordered_string();
-        
-        println!("\n=== Comparing results ===");
-        println!("next_board piece count: {}", next_board.len());
-        println!("prpsd_board piece count: {}", prpsd_board.len());
-        
-        if next_str != prpsd_str {
-            println!("\n=== DETAILED DIFF ===");
-            println!("next_board string length: {}", next_str.len());
-            println!("prpsd_board string length: {}", prpsd_str.len());
-            
-            let next_lines: Vec<_> = next_str.lines().collect();
-            let prpsd_lines: Vec<_> = prpsd_str.lines().collect();
-            
-            println!("next_board lines: {}", next_lines.len());
-            println!("prpsd_board lines: {}", prpsd_lines.len());
-            
-            let max_len = next_lines.len().max(prpsd_lines.len());
-            for i in 0..max_len {
-                let n = next_lines.get(i).unwrap_or(&"");
-                let p = prpsd_lines.get(i).unwrap_or(&"");
-                if n != p {
-                    println!("Line {}:", i);
-                    println!("- Next:   '{}'", n);
-                    println!("+ Prpsd:  '{}'", p);
-                }
-            }
-            
-            println!("\n=== FULL BOARD CONTENTS ===");
-            println!("next_board:\n {}", next_board);
-            println!("prpsd_board:\n {}", prpsd_board);
+impl Board {
+    /// The kind of move the incremental update cannot play, "promotions" or
+    /// "castling", if `mv` is one; `pre_processed_move` falls back to the full
+    /// rebuild for castling.
+    pub fn pre_processed_unsupported(&self, mv: Move) -> Option<&'static str> {
+        if mv.promotion.is_some() {
+            return Some("promotions");
         }
-        
-        assert_eq!(
-            next_str, prpsd_str,
-            "Board string representations differ, see diff above"
-        );
-        
-        println!("=================================== TEST PASSED! ====================================");
+        let pchar = self.get_piece_on(mv.from)?.get_piece_type_as_char();
+        Board::castling_rook_squares(pchar, mv.from, mv.to).map(|_| "castling")
+    }
 
-        // board = prpsd_board;
-        board = next_board.clone();
+    /// Plays `mv` through the full rebuild and through the incremental update, in
+    /// that order. The incremental board is `None` for the moves named by
+    /// `pre_processed_unsupported`, which only the full rebuild can play.
+    pub fn play_both_paths(&self, mv: Move) -> Result<(Board, Option<Board>)> {
+        let full = self.full_process(mv.from, mv.to, mv.promotion)?;
+        let incremental = match self.pre_processed_unsupported(mv) {
+            Some(_) => None,
+            None => Some(self.pre_processed_unvalidated(mv.from, mv.to)?),
+        };
+        Ok((full, incremental))
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square::*;
 
+    /// Plays `moves` from the named position through both paths, failing on the
+    /// first divergence `run_scenario` reports.
+    fn move_test_framework(position: &str, moves: Vec<&str>) {
+        let scenario = crate::scenario::Scenario {
+            name: position.to_string(),
+            start: Board::named(position).unwrap(),
+            moves: moves.into_iter().map(str::to_string).collect(),
+            expectations: Vec::new(),
+        };
+        let outcome = crate::scenario::run_scenario(&scenario);
+        assert!(outcome.passed(), "{outcome}");
+    }
 
+    #[test]
+    fn test_square_file_rank_api() {
+        assert_eq!(e4.file(), File::E);
+        assert_eq!(e4.rank(), Rank::R4);
+        assert_eq!(e4.index(), 28);
+        assert_eq!(Square::from_index(28), Some(e4));
+        assert_eq!(Square::from_index(64), None);
+        assert_eq!(Square::from_file_rank(File::H, Rank::R8), h8);
+        assert_eq!(Square::from_chars('c', '6'), Some(c6));
+        assert_eq!(Square::from_chars('i', '6'), None);
+        assert_eq!(a1.distance(h8), 7);
+        assert_eq!(e4.distance(f6), 2);
+        assert_eq!(a1.colour(), SquareColour::Dark);
+        assert_eq!(h1.colour(), SquareColour::Light);
+        assert_eq!(b6.en_passant_victim(), b5);
+        assert_eq!(e3.en_passant_victim(), e4);
+        assert_eq!(File::G.to_char(), 'g');
+        assert_eq!(Rank::from_char('3'), Some(Rank::R3));
+    }
 
+    #[test]
+    fn test_malformed_input_is_reported_not_panicked() {
+        let mut board = Board::new();
+        assert_eq!(board.create_and_place_piece("z9K"), Err(Error::InvalidPid("z9K".to_string())));
+        assert!(board.init_from_pid_list(vec!["e1K", "e8x"]).is_err());
+        board.build_all_xchngrs().unwrap();
+
+        assert_eq!(board.full_process_move(d4, d5).unwrap_err(), Error::EmptySquare(d4));
+        assert_eq!(board.pre_processed_move(d4, d5).unwrap_err(), Error::EmptySquare(d4));
+        assert!(matches!(
+            board.pre_processed_move(e1, g4),
+            Err(Error::IllegalMove { from: e1, to: g4, .. })
+        ));
+
+        // a pawn on the last rank without a promotion piece is no position at all
+        let promoting = Board::from_fen("7k/P7/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        for play in [Board::full_process_move, Board::pre_processed_move] {
+            assert!(matches!(play(&promoting, a7, a8), Err(Error::IllegalMove { from: a7, to: a8, .. })));
+        }
+        assert_eq!(promoting.full_process(a7, a8, Some('N')).unwrap().to_fen(), "N6k/8/8/8/8/8/8/K7 b - - 0 1");
+    }
 
+    #[test]
+    fn test_king_steps_off_a_pin_line() {
+        // the king's e-file chain is a pin marker, not a piece bearing on e1
+        let board = Board::named("pinned_knight").unwrap();
+        let full = board.full_process_move(e1, d1).unwrap();
+        let prpsd = board.pre_processed_move(e1, d1).unwrap();
+        assert_eq!(prpsd.diff(&full), vec![]);
+
+        // taking the knight leaves it on no chain, and the queen now backs the rook on e2
+        let full = full.full_process_move(e7, e2).unwrap();
+        let prpsd = prpsd.pre_processed_move(e7, e2).unwrap();
+        assert_eq!(prpsd.diff(&full), vec![]);
+        assert_eq!(prpsd.get_piece_on(e8).unwrap().exchangers.get(&Direction::S).map(String::as_str), Some("e2r"));
+    }
 
+    #[test]
+    fn test_processing_stats_accumulate_per_path() {
+        let board = Board::named("double_discovered_check").unwrap();
+
+        let full = board.full_process_move(g2, f1).unwrap();
+        let prpsd = board.pre_processed_move(g2, f1).unwrap();
+        assert_eq!(full.processing_stats().full_moves, 1);
+        assert_eq!(full.processing_stats().pre_processed_moves, 0);
+        assert_eq!(prpsd.processing_stats().pre_processed_moves, 1);
+
+        let last = prpsd.processing_stats().last_move.clone().unwrap();
+        assert_eq!((last.from, last.to, last.path), (g2, f1, ProcessingPath::PreProcessed));
+        assert!(last.exchanger_updates > 0);
+        // both paths count the chains the move changed, so the same move counts the same
+        assert_eq!(full.processing_stats().last_move.clone().unwrap().exchanger_updates, last.exchanger_updates);
+        assert_eq!(full.processing_stats().full_exchanger_updates, prpsd.processing_stats().pre_processed_exchanger_updates);
+
+        // castling falls back to the rebuild but was asked of the pre-processed path
+        let castles = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap().pre_processed_move(e1, g1).unwrap();
+        assert_eq!(castles.processing_stats().pre_processed_moves, 1);
+        assert_eq!(castles.processing_stats().full_moves, 0);
+
+        let next = prpsd.pre_processed_move(b7, b5).unwrap();
+        assert_eq!(next.processing_stats().pre_processed_moves, 2);
+        assert!(next.processing_stats().mean_pre_processed_nanos().is_some());
+        assert!(next.processing_stats().mean_full_nanos().is_none());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::board::Square::*;
+    #[test]
+    fn test_validate_reports_each_corruption() {
+        let board = Board::named("double_discovered_check").unwrap();
+        assert_eq!(board.validate(), Ok(()));
+
+        let mut stray_bit = board.clone();
+        stray_bit.occupied |= 1u64 << h8.index();
+        assert!(matches!(stray_bit.validate(), Err(Error::CorruptBoard(v)) if v.iter().any(|m| m.contains("occupied bitboard"))));
+        assert!(stray_bit.validate_structure().is_err());
+
+        let mut wrong_king = board.clone();
+        wrong_king.white_king_location = Some(e2);
+        assert!(matches!(wrong_king.validate(), Err(Error::CorruptBoard(v)) if v.iter().any(|m| m.contains("recorded on e2"))));
+
+        let mut stale = board.clone();
+        stale.pieces.get_mut(&a1).unwrap().exchangers.insert(Direction::N, "a5Pa6k".to_string());
+        let Err(Error::CorruptBoard(violations)) = stale.validate() else { panic!("expected corruption") };
+        assert!(violations.iter().any(|m| m == "a1R N expected - got a5Pa6k"));
+        // a wrong but well-formed chain takes the rebuild to find
+        assert_eq!(stale.validate_structure(), Ok(()));
+
+        let mut dangling = board.clone();
+        dangling.pieces.get_mut(&a1).unwrap().exchangers.insert(Direction::E, "c1Q".to_string());
+        let Err(Error::CorruptBoard(violations)) = dangling.validate() else { panic!("expected corruption") };
+        assert!(violations.iter().any(|m| m == "a1R E: c1Q is not on c1"));
+    }
 
-    fn move_test_framework(pids: Vec<&str>, moves: Vec<(Square, Square)>) {
+    #[test]
+    fn test_renderers_show_malformed_chains() {
+        assert!(matches!(crate::pid::split_chain("e7rh7"), Err(Error::InconsistentExchangers(_))));
+        assert!(matches!(crate::pid::split_chain("<"), Err(Error::InconsistentExchangers(_))));
+        assert_eq!(crate::pid::split_chain(">e2ne7R").unwrap().1.len(), 2);
+
+        let mut board = Board::from_fen("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+        board.pieces.get_mut(&e2).unwrap().exchangers.insert(Direction::N, "e7xe8".to_string());
+        assert_eq!(board.square_overlay(e2).malformed, vec![Direction::N]);
+        assert!(board.diagram(&DiagramOptions::ascii().with_focus(e2)).ends_with("? malformed exchangers on e2: N"));
+        assert!(board.to_dot().contains(r#""?e7xe8" -> "e2N" [label="N ?"];"#));
+        assert!(board.to_svg(&crate::svg::SvgOptions::default()).contains(r#"<text class="malformed""#));
+    }
 
-        println!("=== Using move_test_framework for position: {pids:?} and moves: {moves:?} ===");
-        
-        let mut board = Board::new();
-        board.init_from_pid_list(pids);
-        board.build_all_xchngrs();
-        
-        for (from,  to) in moves {
-            let next_board = board
-                .full_process_move(from, to);
-            println!("Maximally processed exchangers post move:\n {}", next_board);
-            
-            let prpsd_board = board
-                .pre_processed_move(from, to);
-            println!("Pre-processed move completed");
-        
-            let next_str = next_board.to_ordered_string();
-            let prpsd_str = prpsd_board.to_ordered_string();
-            
-            println!("\n=== Comparing results ===");
-            println!("next_board piece count: {}", next_board.len());
-            println!("prpsd_board piece count: {}", prpsd_board.len());
-            
-            if next_str != prpsd_str {
-                println!("\n=== DETAILED DIFF ===");
-                println!("next_board string length: {}", next_str.len());
-                println!("prpsd_board string length: {}", prpsd_str.len());
-                
-                let next_lines: Vec<_> = next_str.lines().collect();
-                let prpsd_lines: Vec<_> = prpsd_str.lines().collect();
-                
-                println!("next_board lines: {}", next_lines.len());
-                println!("prpsd_board lines: {}", prpsd_lines.len());
-                
-                let max_len = next_lines.len().max(prpsd_lines.len());
-                for i in 0..max_len {
-                    let n = next_lines.get(i).unwrap_or(&"");
-                    let p = prpsd_lines.get(i).unwrap_or(&"");
-                    if n != p {
-                        println!("Line {}:", i);
-                        println!("- Next:   '{}'", n);
-                        println!("+ Prpsd:  '{}'", p);
-                    }
-                }
-                
-                println!("\n=== FULL BOARD CONTENTS ===");
-                println!("next_board:\n {}", next_board);
-                println!("\nprpsd_board:\n {}", prpsd_board);
-            }
-            
-            assert_eq!(
-                next_str, prpsd_str,
-                "Board string representations differ, see diff above"
-            );
-            
-            println!("=== Test passed! ===");
+    #[test]
+    fn test_king_locations_and_check_classification() {
+        let board = Board::named("double_discovered_check").unwrap();
+        assert_eq!(board.king_location(Side::White), Some(e1));
+        assert_eq!(board.king_location(Side::Black), Some(a6));
+
+        for play in [Board::full_process_move, Board::pre_processed_move] {
+            let bishop_check = play(&board, g2, f1).unwrap();
+            assert_eq!(bishop_check.check_kind(), Some(CheckKind::Direct));
+            let double_step = play(&bishop_check, b7, b5).unwrap();
+            assert_eq!(double_step.check_kind(), None);
+            // a5xb6 e.p. empties both a5 and b5, opening the rook's file and the bishop's diagonal
+            let ep = play(&double_step, a5, b6).unwrap();
+            let checkers: Vec<&str> = ep.checks().iter().map(|pid| pid.as_str()).collect();
+            assert_eq!(checkers, vec!["a1R", "f1B"]);
+            assert_eq!(ep.check_kind(), Some(CheckKind::Double));
+
+            let king_move = play(&ep, a6, b6).unwrap();
+            assert_eq!(king_move.king_location(Side::Black), Some(b6));
+            assert!(king_move.checks().is_empty());
+        }
 
-            board = prpsd_board;
+        let mut lines = Board::new();
+        lines.init_from_pid_list(vec!["a1K", "e8k", "e1R", "e4N", "h2R"]).unwrap();
+        lines.build_all_xchngrs().unwrap();
+        let kinds = [(h2, h8, CheckKind::Direct), (e4, c3, CheckKind::Discovered), (e4, d6, CheckKind::Double)];
+        for (from, to, kind) in kinds {
+            assert_eq!(lines.full_process_move(from, to).unwrap().check_kind(), Some(kind));
         }
+        assert_eq!(lines.full_process_move(a1, b1).unwrap().check_kind(), None);
     }
 
     #[test]
     fn test_double_discovered_mate_move_sequence() {
-        move_test_framework(
-            vec!["e1K", "a6k", "a5P", "b7p", "a1R", "g2B", "g1B", "d8N"],
-            vec![(g2, f1), (b7, b5)]
-        );
+        move_test_framework("double_discovered_check", vec!["Bf1+", "b5"]);
     }
 }
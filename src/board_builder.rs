@@ -0,0 +1,147 @@
+// src/board_builder.rs
+// Fluent set-up of a position. The builder takes squares and piece types instead of
+// pids, defaults to White to move with no castling rights, and on `build` checks the
+// position like `from_fen` does and builds the exchangers.
+//
+//   let board = Board::builder()
+//       .piece(e1, WhiteKing)
+//       .piece(e8, BlackKing)
+//       .piece(d4, BlackPawn)
+//       .piece(e4, WhitePawn)
+//       .side_to_move(Side::Black)
+//       .en_passant(e3)
+//       .build()?;
+use crate::board::{Board, CastlingRights, Square};
+use crate::error::{Error, Result};
+use crate::legality::{check_position, Violation};
+use crate::pieces::{PieceType, Side};
+
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    pieces: Vec<(Square, PieceType)>,
+    turn: Side,
+    castling: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        BoardBuilder::new()
+    }
+}
+
+impl BoardBuilder {
+    pub fn new() -> Self {
+        BoardBuilder {
+            pieces: Vec::new(),
+            turn: Side::White,
+            castling: CastlingRights::NONE,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    pub fn piece(mut self, square: Square, piece_type: PieceType) -> Self {
+        self.pieces.push((square, piece_type));
+        self
+    }
+
+    pub fn side_to_move(mut self, side: Side) -> Self {
+        self.turn = side;
+        self
+    }
+
+    pub fn castling(mut self, rights: CastlingRights) -> Self {
+        self.castling = rights;
+        self
+    }
+
+    /// The square a pawn skipped on its double step, as in FEN.
+    pub fn en_passant(mut self, square: Square) -> Self {
+        self.en_passant = Some(square);
+        self
+    }
+
+    pub fn move_clocks(mut self, halfmove_clock: u32, fullmove_number: u32) -> Self {
+        self.halfmove_clock = halfmove_clock;
+        self.fullmove_number = fullmove_number;
+        self
+    }
+
+    /// Places the pieces and builds the exchangers, or reports every reason the
+    /// position could not arise in a game, a square given twice among them.
+    pub fn build(&self) -> Result<Board> {
+        let mut board = Board::new();
+        let mut violations = Vec::new();
+        for (square, piece_type) in &self.pieces {
+            if board.is_square_occupied(*square) && !violations.contains(&Violation::DuplicateSquare(*square)) {
+                violations.push(Violation::DuplicateSquare(*square));
+            }
+            board.create_and_place_piece(&format!("{square}{}", piece_type.to_char()))?;
+        }
+        board.set_turn(self.turn);
+        board.set_castling_rights(self.castling);
+        board.set_en_passant_square(self.en_passant);
+        board.set_move_clocks(self.halfmove_clock, self.fullmove_number);
+
+        violations.extend(check_position(&board));
+        if !violations.is_empty() {
+            return Err(Error::IllegalPosition(violations));
+        }
+        board.build_all_xchngrs()?;
+        Ok(board)
+    }
+}
+
+impl Board {
+    pub fn builder() -> BoardBuilder {
+        BoardBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square::*;
+    use crate::pieces::PieceType::*;
+
+    #[test]
+    fn test_builder_matches_fen() {
+        let board = Board::builder()
+            .piece(e1, WhiteKing)
+            .piece(h1, WhiteRook)
+            .piece(e8, BlackKing)
+            .piece(d4, BlackPawn)
+            .piece(e4, WhitePawn)
+            .side_to_move(Side::Black)
+            .castling(CastlingRights { white_king_side: true, ..CastlingRights::NONE })
+            .en_passant(e3)
+            .move_clocks(0, 12)
+            .build()
+            .unwrap();
+        let from_fen = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 12").unwrap();
+        assert_eq!(board.to_fen(), from_fen.to_fen());
+        assert!(board.diff(&from_fen).is_empty());
+        assert!(board.validate().is_ok());
+    }
+
+    #[test]
+    fn test_builder_validates() {
+        let kings = Board::builder().piece(e1, WhiteKing).piece(e8, BlackKing);
+        let Err(Error::IllegalPosition(violations)) = kings.clone().piece(e1, WhiteQueen).build() else {
+            panic!("a square given twice must be rejected");
+        };
+        assert_eq!(violations[0], Violation::DuplicateSquare(e1));
+
+        let Err(Error::IllegalPosition(violations)) = kings.clone().en_passant(e3).build() else {
+            panic!("an en passant square without a pawn must be rejected");
+        };
+        assert!(matches!(violations[..], [Violation::EnPassant { square: e3, .. }]));
+
+        assert!(matches!(Board::builder().piece(e1, WhiteKing).build(), Err(Error::IllegalPosition(_))));
+        assert_eq!(kings.build().unwrap().turn(), Side::White);
+    }
+}
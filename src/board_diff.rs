@@ -0,0 +1,230 @@
+// src/board_diff.rs
+// Structured comparison of two boards, the first taken as expected and the second
+// as actual. Used to explain where the incremental exchanger update and the full
+// rebuild part ways, one line per difference.
+use crate::attacks::squares_of;
+use crate::board::{Board, CastlingRights, CheckKind, Square};
+use crate::compass_groups::Direction;
+use crate::moves::Move;
+use crate::pid::Pid;
+use crate::pieces::Side;
+use std::fmt;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoardDifference {
+    /// A piece only on one board (`None` on the other), or different pieces on the square.
+    Piece { square: Square, expected: Option<Pid>, actual: Option<Pid> },
+    /// An exchanger direction only on one board, or with different chains. Compared
+    /// only where both boards hold the same piece.
+    Exchanger { pid: Pid, direction: Direction, expected: Option<String>, actual: Option<String> },
+    /// The occupied bitboards disagree, even though the pieces may not.
+    Occupied { expected: u64, actual: u64 },
+    Moves { expected: Vec<Move>, actual: Vec<Move> },
+    Turn { expected: Side, actual: Side },
+    Castling { expected: CastlingRights, actual: CastlingRights },
+    EnPassant { expected: Option<Square>, actual: Option<Square> },
+    /// The halfmove clock and the fullmove number.
+    Clocks { expected: (u32, u32), actual: (u32, u32) },
+    Checks { expected: Vec<Pid>, actual: Vec<Pid> },
+    CheckKind { expected: Option<CheckKind>, actual: Option<CheckKind> },
+    /// One board has an attack map and the other has none.
+    AttackMap { expected: bool, actual: bool },
+    /// The attack map's attackers (or x-ray attackers) of `side` on `square`.
+    Attackers { square: Square, side: Side, xray: bool, expected: u64, actual: u64 },
+}
+
+fn or_dash<T: fmt::Display>(value: &Option<T>) -> String {
+    value.as_ref().map_or_else(|| "-".to_string(), T::to_string)
+}
+
+fn square_list(bits: u64) -> String {
+    let squares: Vec<String> = squares_of(bits).map(|square| square.to_string()).collect();
+    if squares.is_empty() { "-".to_string() } else { squares.join(",") }
+}
+
+fn move_list(moves: &[Move]) -> String {
+    let moves: Vec<String> = moves.iter().map(Move::to_uci).collect();
+    format!("[{}]", moves.join(" "))
+}
+
+fn pid_list(pids: &[Pid]) -> String {
+    let pids: Vec<&str> = pids.iter().map(Pid::as_str).collect();
+    format!("[{}]", pids.join(","))
+}
+
+impl fmt::Display for BoardDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardDifference::Piece { square, expected, actual } => {
+                write!(f, "{square} expected {} got {}", or_dash(expected), or_dash(actual))
+            }
+            BoardDifference::Exchanger { pid, direction, expected, actual } => {
+                write!(f, "{pid} {direction} expected {} got {}", or_dash(expected), or_dash(actual))
+            }
+            BoardDifference::Occupied { expected, actual } => {
+                write!(f, "occupied expected {expected:#018x} got {actual:#018x}")
+            }
+            BoardDifference::Moves { expected, actual } => {
+                write!(f, "moves expected {} got {}", move_list(expected), move_list(actual))
+            }
+            BoardDifference::Turn { expected, actual } => write!(f, "turn expected {expected:?} got {actual:?}"),
+            BoardDifference::Castling { expected, actual } => {
+                write!(f, "castling expected {} got {}", expected.to_fen(), actual.to_fen())
+            }
+            BoardDifference::EnPassant { expected, actual } => {
+                write!(f, "en passant expected {} got {}", or_dash(expected), or_dash(actual))
+            }
+            BoardDifference::Clocks { expected, actual } => {
+                write!(f, "clocks expected {} {} got {} {}", expected.0, expected.1, actual.0, actual.1)
+            }
+            BoardDifference::Checks { expected, actual } => {
+                write!(f, "checks expected {} got {}", pid_list(expected), pid_list(actual))
+            }
+            BoardDifference::CheckKind { expected, actual } => {
+                let show = |kind: &Option<CheckKind>| kind.map_or_else(|| "-".to_string(), |kind| format!("{kind:?}"));
+                write!(f, "check kind expected {} got {}", show(expected), show(actual))
+            }
+            BoardDifference::AttackMap { expected, actual } => {
+                let show = |present: &bool| if *present { "present" } else { "absent" };
+                write!(f, "attack map expected {} got {}", show(expected), show(actual))
+            }
+            BoardDifference::Attackers { square, side, xray, expected, actual } => {
+                let what = if *xray { "x-rays" } else { "attackers" };
+                write!(f, "{square} {side:?} {what} expected {} got {}", square_list(*expected), square_list(*actual))
+            }
+        }
+    }
+}
+
+impl Board {
+    /// Everything that differs between `self` (expected) and `other` (actual), by
+    /// square and then direction, followed by the status fields and the attack map.
+    pub fn diff(&self, other: &Board) -> Vec<BoardDifference> {
+        let mut differences = Vec::new();
+        for index in 0..64 {
+            let square = Square::from_index(index).expect("64 squares");
+            let (mine, theirs) = (self.get_piece_on(square), other.get_piece_on(square));
+            let (expected, actual) = (mine.map(|p| p.get_pid().clone()), theirs.map(|p| p.get_pid().clone()));
+            if expected != actual {
+                differences.push(BoardDifference::Piece { square, expected, actual });
+                continue;
+            }
+            let (Some(mine), Some(theirs)) = (mine, theirs) else { continue };
+            for direction in Direction::iter() {
+                let (expected, actual) = (mine.exchangers.get(&direction), theirs.exchangers.get(&direction));
+                if expected != actual {
+                    differences.push(BoardDifference::Exchanger {
+                        pid: mine.get_pid().clone(),
+                        direction,
+                        expected: expected.cloned(),
+                        actual: actual.cloned(),
+                    });
+                }
+            }
+        }
+        if self.get_occupied_bitboard() != other.get_occupied_bitboard() {
+            differences.push(BoardDifference::Occupied {
+                expected: self.get_occupied_bitboard(),
+                actual: other.get_occupied_bitboard(),
+            });
+        }
+        if self.moves() != other.moves() {
+            differences.push(BoardDifference::Moves { expected: self.moves().to_vec(), actual: other.moves().to_vec() });
+        }
+        if self.turn() != other.turn() {
+            differences.push(BoardDifference::Turn { expected: self.turn(), actual: other.turn() });
+        }
+        if self.castling_rights() != other.castling_rights() {
+            differences.push(BoardDifference::Castling { expected: self.castling_rights(), actual: other.castling_rights() });
+        }
+        if self.en_passant_square() != other.en_passant_square() {
+            differences.push(BoardDifference::EnPassant { expected: self.en_passant_square(), actual: other.en_passant_square() });
+        }
+        let clocks = |board: &Board| (board.halfmove_clock(), board.fullmove_number());
+        if clocks(self) != clocks(other) {
+            differences.push(BoardDifference::Clocks { expected: clocks(self), actual: clocks(other) });
+        }
+        if self.checks() != other.checks() {
+            differences.push(BoardDifference::Checks { expected: self.checks().to_vec(), actual: other.checks().to_vec() });
+        }
+        if self.check_kind() != other.check_kind() {
+            differences.push(BoardDifference::CheckKind { expected: self.check_kind(), actual: other.check_kind() });
+        }
+        self.diff_attack_maps(other, &mut differences);
+        differences
+    }
+
+    fn diff_attack_maps(&self, other: &Board, differences: &mut Vec<BoardDifference>) {
+        let (mine, theirs) = match (self.attack_map(), other.attack_map()) {
+            (Some(mine), Some(theirs)) => (mine, theirs),
+            (None, None) => return,
+            (mine, theirs) => {
+                differences.push(BoardDifference::AttackMap { expected: mine.is_some(), actual: theirs.is_some() });
+                return;
+            }
+        };
+        for index in 0..64 {
+            let square = Square::from_index(index).expect("64 squares");
+            for side in [Side::White, Side::Black] {
+                for xray in [false, true] {
+                    let (expected, actual) = if xray {
+                        (mine.xray_attackers(square, side), theirs.xray_attackers(square, side))
+                    } else {
+                        (mine.attackers(square, side), theirs.attackers(square, side))
+                    };
+                    if expected != actual {
+                        differences.push(BoardDifference::Attackers { square, side, xray, expected, actual });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square::*;
+
+    #[test]
+    fn test_diff_reports_each_difference() {
+        let board = Board::from_fen("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+        assert!(board.diff(&board.clone()).is_empty());
+
+        let without_rook = Board::from_fen("4k3/8/8/8/8/8/4N3/4K3 b - - 0 1").unwrap();
+        let differences: Vec<String> = board.diff(&without_rook).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            differences[..6],
+            [
+                "e1K N expected <e2Ne7r got -".to_string(),
+                "e2N N expected e7r got -".to_string(),
+                "e7 expected e7r got -".to_string(),
+                "e8k S expected e7r got -".to_string(),
+                "occupied expected 0x1010000000001010 got 0x1000000000001010".to_string(),
+                "turn expected White got Black".to_string(),
+            ]
+        );
+        assert_eq!(differences[6], "e1 Black x-rays expected e7 got -");
+        assert!(differences.contains(&"d7 Black attackers expected e7,e8 got e8".to_string()));
+
+        let moved = board.play_san("Kd1").unwrap();
+        let differences = board.diff(&moved);
+        assert!(differences.contains(&BoardDifference::Piece { square: d1, expected: None, actual: Some(Pid::new("d1K").unwrap()) }));
+        assert!(differences.iter().any(|d| d.to_string() == "e2N SW expected - got d1K"));
+        assert!(differences.iter().any(|d| d.to_string() == "moves expected [] got [e1d1]"));
+        assert!(differences.iter().any(|d| d.to_string() == "clocks expected 0 1 got 1 1"));
+
+        let stripped = board.without_exchangers();
+        assert!(board.diff(&stripped).contains(&BoardDifference::AttackMap { expected: true, actual: false }));
+
+        let mut castled = board.clone();
+        castled.set_castling_rights(CastlingRights::ALL);
+        assert_eq!(board.diff(&castled), vec![BoardDifference::Castling { expected: CastlingRights::NONE, actual: CastlingRights::ALL }]);
+
+        let rook = Board::from_fen("4k3/8/8/8/8/8/4N3/R3K3 w - - 0 1").unwrap();
+        let checked = rook.play_san("Ra8+").unwrap();
+        let differences: Vec<String> = checked.diff(&rook.play_san("Ra7").unwrap()).iter().map(ToString::to_string).collect();
+        assert!(differences.contains(&"check kind expected Direct got -".to_string()));
+    }
+}
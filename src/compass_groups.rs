@@ -28,8 +28,47 @@ impl Direction {
             Direction::NNW => Direction::SSE,
         }
     }
+
+    /// Returns the (file, rank) step taken by a single move in this direction.
+    pub const fn delta(&self) -> (i8, i8) {
+        match self {
+            Direction::N => (0, 1),
+            Direction::NNE => (1, 2),
+            Direction::NE => (1, 1),
+            Direction::ENE => (2, 1),
+            Direction::E => (1, 0),
+            Direction::ESE => (2, -1),
+            Direction::SE => (1, -1),
+            Direction::SSE => (1, -2),
+            Direction::S => (0, -1),
+            Direction::SSW => (-1, -2),
+            Direction::SW => (-1, -1),
+            Direction::WSW => (-2, -1),
+            Direction::W => (-1, 0),
+            Direction::WNW => (-2, 1),
+            Direction::NW => (-1, 1),
+            Direction::NNW => (-1, 2),
+        }
+    }
+
+    /// True for the eight knight directions, which only ever take a single step.
+    pub const fn is_half_wind(&self) -> bool {
+        matches!(
+            self,
+            Direction::NNE | Direction::ENE | Direction::ESE | Direction::SSE
+                | Direction::SSW | Direction::WSW | Direction::WNW | Direction::NNW
+        )
+    }
 }
 
+/// All sixteen directions in declaration order, so that `ALL_DIRECTIONS[d as usize] == d`.
+pub const ALL_DIRECTIONS: [Direction; 16] = [
+    Direction::N, Direction::NNE, Direction::NE, Direction::ENE,
+    Direction::E, Direction::ESE, Direction::SE, Direction::SSE,
+    Direction::S, Direction::SSW, Direction::SW, Direction::WSW,
+    Direction::W, Direction::WNW, Direction::NW, Direction::NNW,
+];
+
 use lazy_static::lazy_static;
 lazy_static! {
     pub static ref CARDINALS: Vec<Direction>
@@ -57,35 +96,8 @@ pub const DIRECTION_PAIRS: [(Direction, Direction); 8] = [
 ];
 
 pub fn get_direction(from: Square, to: Square) -> Option<Direction> {
-    fn parse_square(square: &str) -> Option<(isize, isize)> {
-        let mut chars = square.chars();
-        let file_char = chars.next()?;
-        let rank_char = chars.next()?;
-    
-        if chars.next().is_some()
-            || !file_char.is_ascii_alphabetic()
-                || !rank_char.is_ascii_digit() {
-            return None;
-        }
-    
-        let file = (file_char.to_ascii_lowercase() as isize) - ('a' as isize);
-        let rank = (rank_char.to_digit(10)? as isize) - 1;
-    
-        if (0..=7).contains(&file) && (0..=7).contains(&rank) {
-            Some((file, rank))
-        } else {
-            None
-        }
-    }
-    
-    let from_str: &str = from.as_ref();
-    let to_str: &str = to.as_ref();
-
-    let (from_file, from_rank) = parse_square(from_str)?;
-    let (to_file, to_rank) = parse_square(to_str)?;
-
-    let file_diff = to_file - from_file;
-    let rank_diff = to_rank - from_rank;
+    let file_diff = to.file() as isize - from.file() as isize;
+    let rank_diff = to.rank() as isize - from.rank() as isize;
 
     // Use a match statement to handle all move vectors.
     let direction = match (file_diff, rank_diff) {
@@ -0,0 +1,383 @@
+// src/diagram.rs
+// Text diagrams of a board, White at the bottom. Pieces are drawn as letters or
+// Unicode figurines, optionally on ANSI coloured squares. An overlay picks out the
+// attackers and defenders of one square, read from its exchanger chains, and the
+// pieces pinned to their king. `Board::from_diagram` reads such a picture back.
+use crate::attacks::{attackers_of, squares_of};
+use crate::board::{Board, File, Rank, Square};
+use crate::compass_groups::Direction;
+use crate::error::{Error, Result};
+use crate::pid::split_chain;
+use crate::pieces::{figurine, PieceType, Side};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyphs {
+    Ascii,
+    Unicode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagramOptions {
+    pub glyphs: Glyphs,
+    /// ANSI colours for the squares, pieces and overlay.
+    pub colour: bool,
+    /// The square whose attackers and defenders are highlighted.
+    pub focus: Option<Square>,
+    pub show_pins: bool,
+}
+
+impl DiagramOptions {
+    pub fn ascii() -> Self {
+        DiagramOptions { glyphs: Glyphs::Ascii, colour: false, focus: None, show_pins: false }
+    }
+
+    pub fn unicode() -> Self {
+        DiagramOptions { glyphs: Glyphs::Unicode, ..DiagramOptions::ascii() }
+    }
+
+    pub fn with_colour(mut self) -> Self {
+        self.colour = true;
+        self
+    }
+
+    pub fn with_focus(mut self, square: Square) -> Self {
+        self.focus = Some(square);
+        self
+    }
+
+    pub fn with_pins(mut self) -> Self {
+        self.show_pins = true;
+        self
+    }
+}
+
+/// Who bears on a square. For an occupied square the pieces come from its
+/// exchanger chains and sides are relative to the occupant; an empty square has no
+/// exchangers, so its attackers are computed, sides relative to the side to move.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SquareOverlay {
+    pub attackers: Vec<Square>,
+    pub defenders: Vec<Square>,
+    /// Directions whose exchanger chain cannot be read, a sign of a corrupt board.
+    pub malformed: Vec<Direction>,
+}
+
+impl Board {
+    pub fn square_overlay(&self, square: Square) -> SquareOverlay {
+        let mut overlay = SquareOverlay::default();
+        match self.get_piece_on(square) {
+            Some(piece) => {
+                let owner = piece.get_side();
+                for (drctn, chain) in &piece.exchangers {
+                    let pids = match split_chain(chain) {
+                        Ok((None, pids)) => pids,
+                        // marked chains are pin lines through the square, not attacks on it
+                        Ok(_) => continue,
+                        Err(_) => {
+                            overlay.malformed.push(*drctn);
+                            continue;
+                        }
+                    };
+                    for pid in pids {
+                        let list = if pid.clone().get_side() == owner { &mut overlay.defenders } else { &mut overlay.attackers };
+                        list.push(pid.square());
+                    }
+                }
+            }
+            None => {
+                let owner = self.turn();
+                overlay.defenders = squares_of(attackers_of(self, square, owner)).collect();
+                overlay.attackers = squares_of(attackers_of(self, square, owner.opposite())).collect();
+            }
+        }
+        overlay.attackers.sort_by_key(|sq| sq.index());
+        overlay.defenders.sort_by_key(|sq| sq.index());
+        overlay.malformed.sort();
+        overlay
+    }
+
+    /// The pieces standing between their own king and an enemy slider.
+    pub fn pinned_pieces(&self) -> Vec<Square> {
+        let mut pinned: Vec<Square> = self
+            .pin_lines()
+            .into_iter()
+            .filter_map(|(_, _, chain)| match split_chain(chain) {
+                Ok((Some('<'), pids)) => pids.first().map(|pid| pid.square()),
+                _ => None,
+            })
+            .collect();
+        pinned.sort_by_key(|sq| sq.index());
+        pinned
+    }
+
+    pub fn diagram(&self, options: &DiagramOptions) -> String {
+        let overlay = options.focus.map(|square| self.square_overlay(square)).unwrap_or_default();
+        let pinned = if options.show_pins { self.pinned_pieces() } else { Vec::new() };
+        let role = |square: Square| {
+            if options.focus == Some(square) {
+                Some(Role::Focus)
+            } else if overlay.attackers.contains(&square) {
+                Some(Role::Attacker)
+            } else if overlay.defenders.contains(&square) {
+                Some(Role::Defender)
+            } else if pinned.contains(&square) {
+                Some(Role::Pinned)
+            } else {
+                None
+            }
+        };
+
+        let mut out = String::new();
+        for rank in (0..8).rev() {
+            out.push(Rank::from_index(rank).expect("eight ranks").to_char());
+            out.push(' ');
+            for file in 0..8 {
+                let square = Square::from_file_rank(File::from_index(file).expect("eight files"), Rank::from_index(rank).expect("eight ranks"));
+                let glyph = self.get_piece_on(square).map(|p| p.get_piece_type_as_char());
+                out.push_str(&cell(glyph, role(square), (file + rank) % 2 == 1, options));
+            }
+            out.push('\n');
+        }
+        out.push_str("   a  b  c  d  e  f  g  h");
+        if options.focus.is_some() || options.show_pins {
+            out.push('\n');
+            out.push_str(if options.colour {
+                "yellow: focus  red: attackers  green: defenders  underlined: pinned"
+            } else {
+                "[ ] focus  ( ) attackers  { } defenders  < > pinned"
+            });
+        }
+        if !overlay.malformed.is_empty() {
+            let directions: Vec<String> = overlay.malformed.iter().map(ToString::to_string).collect();
+            out.push_str(&format!("\n? malformed exchangers on {}: {}", options.focus.expect("overlay of the focus"), directions.join(", ")));
+        }
+        out
+    }
+}
+
+fn invalid(line: usize, reason: impl Into<String>) -> Error {
+    Error::InvalidDiagram { line, reason: reason.into() }
+}
+
+/// The line without its ANSI escape sequences, so coloured diagrams read like plain ones.
+fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+        } else if chars.next() == Some('[') {
+            // a control sequence runs up to its final byte, '@' to '~'
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    plain
+}
+
+/// The side to move from a line such as "w", "b", "white" or "Black to move".
+fn side_line(line: &str) -> Option<Side> {
+    let line = line.to_ascii_lowercase();
+    match line.strip_suffix("to move").unwrap_or(&line).trim() {
+        "w" | "white" => Some(Side::White),
+        "b" | "black" => Some(Side::Black),
+        _ => None,
+    }
+}
+
+impl Board {
+    /// Reads an 8-line diagram, rank 8 first, with a letter per piece and '.' for an
+    /// empty square, e.g. the output of `diagram`. Rank numbers, the file letters
+    /// line, overlay marks, Unicode figurines and ANSI colours are accepted too. An optional line
+    /// such as "b" or "Black to move" sets the side to move, White by default; the
+    /// position is set up without castling rights or en passant square.
+    pub fn from_diagram(text: &str) -> Result<Board> {
+        let mut builder = Board::builder();
+        let mut rows = 0;
+        for (index, raw) in text.lines().enumerate() {
+            let number = index + 1;
+            let plain = strip_ansi(raw);
+            let line = plain.trim();
+            if line.is_empty() || line.starts_with("[ ] focus") || line.starts_with("yellow: focus") {
+                continue;
+            }
+            if let Some(side) = side_line(line) {
+                builder = builder.side_to_move(side);
+                continue;
+            }
+            let mut cells: Vec<char> = line.chars().filter(|c| !c.is_whitespace() && !"[](){}<>".contains(*c)).collect();
+            if cells.iter().collect::<String>() == "abcdefgh" {
+                continue;
+            }
+            if rows == 8 {
+                return Err(invalid(number, "more than 8 ranks"));
+            }
+            let rank = Rank::from_index(7 - rows).expect("eight ranks");
+            if cells.len() == 9 && cells[0].is_ascii_digit() {
+                if cells[0] != rank.to_char() {
+                    return Err(invalid(number, format!("expected rank {}, found {}", rank.to_char(), cells[0])));
+                }
+                cells.remove(0);
+            }
+            if cells.len() != 8 {
+                return Err(invalid(number, format!("expected 8 squares, found {}", cells.len())));
+            }
+            for (file, glyph) in cells.into_iter().enumerate() {
+                if glyph == '.' || glyph == '·' {
+                    continue;
+                }
+                let letter = "KQRBNPkqrbnp".chars().find(|&letter| figurine(letter) == glyph).unwrap_or(glyph);
+                let piece_type = PieceType::from_char(letter)
+                    .ok_or_else(|| invalid(number, format!("'{glyph}' is not a piece or '.'")))?;
+                let square = Square::from_file_rank(File::from_index(file).expect("eight files"), rank);
+                builder = builder.piece(square, piece_type);
+            }
+            rows += 1;
+        }
+        if rows != 8 {
+            return Err(invalid(0, format!("expected 8 ranks, found {rows}")));
+        }
+        builder.build()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Focus,
+    Attacker,
+    Defender,
+    Pinned,
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// One square, three columns wide.
+fn cell(piece: Option<char>, role: Option<Role>, light: bool, options: &DiagramOptions) -> String {
+    let glyph = match (piece, options.glyphs) {
+        (Some(pchar), Glyphs::Ascii) => pchar,
+        (Some(pchar), Glyphs::Unicode) => figurine(pchar),
+        (None, Glyphs::Ascii) => '.',
+        (None, Glyphs::Unicode) => '·',
+    };
+    if !options.colour {
+        let (open, close) = match role {
+            Some(Role::Focus) => ('[', ']'),
+            Some(Role::Attacker) => ('(', ')'),
+            Some(Role::Defender) => ('{', '}'),
+            Some(Role::Pinned) => ('<', '>'),
+            None => (' ', ' '),
+        };
+        return format!("{open}{glyph}{close}");
+    }
+    let background = match role {
+        Some(Role::Focus) => 178,
+        Some(Role::Attacker) => 167,
+        Some(Role::Defender) => 107,
+        _ if light => 180,
+        _ => 137,
+    };
+    let foreground = match piece.map(|p| if p.is_uppercase() { Side::White } else { Side::Black }) {
+        Some(Side::White) => 231,
+        _ => 16,
+    };
+    let underline = if role == Some(Role::Pinned) { "\x1b[4m" } else { "" };
+    format!("\x1b[48;5;{background}m\x1b[38;5;{foreground}m{underline} {glyph} {RESET}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square::*;
+
+    #[test]
+    fn test_ascii_and_unicode_diagrams() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        let expected = "\
+8  .  .  .  .  k  .  .  . \n\
+7  .  .  .  .  .  .  .  . \n\
+6  .  .  .  .  .  .  .  . \n\
+5  .  .  .  .  .  .  .  . \n\
+4  .  .  .  .  .  .  .  . \n\
+3  .  .  .  .  .  .  .  . \n\
+2  .  .  .  .  P  .  .  . \n\
+1  .  .  .  .  K  .  .  . \n   a  b  c  d  e  f  g  h";
+        assert_eq!(board.to_string(), expected);
+        let unicode = board.diagram(&DiagramOptions::unicode());
+        assert!(unicode.starts_with("8  ·  ·  ·  ·  ♚ "));
+        let coloured = board.diagram(&DiagramOptions::unicode().with_colour());
+        // a1 is dark, so the e1 king sits on a dark square
+        assert!(coloured.contains("\x1b[48;5;137m\x1b[38;5;231m ♔ \x1b[0m"));
+    }
+
+    #[test]
+    fn test_overlay_marks_attackers_defenders_and_pins() {
+        // the e2 knight is pinned by the e7 rook; d4 is attacked by the c6 knight
+        // and defended by the c3 pawn
+        let board = Board::from_fen("4k3/4r3/2n5/8/3N4/2P5/4N3/4K3 w - - 0 1").unwrap();
+        let overlay = board.square_overlay(d4);
+        assert_eq!(overlay, SquareOverlay { attackers: vec![c6], defenders: vec![e2, c3], malformed: vec![] });
+        assert_eq!(board.pinned_pieces(), vec![e2]);
+
+        let diagram = board.diagram(&DiagramOptions::ascii().with_focus(d4).with_pins());
+        let lines: Vec<&str> = diagram.lines().collect();
+        assert_eq!(lines[2], "6  .  . (n) .  .  .  .  . ");
+        assert_eq!(lines[4], "4  .  .  . [N] .  .  .  . ");
+        assert_eq!(lines[5], "3  .  . {P} .  .  .  .  . ");
+        assert_eq!(lines[6], "2  .  .  .  . {N} .  .  . ");
+        assert!(lines[9].starts_with("[ ] focus"));
+
+        let pins_only = board.diagram(&DiagramOptions::ascii().with_pins());
+        assert!(pins_only.contains("2  .  .  .  . <N> .  .  . "));
+        // an empty square has no exchangers, so its attacks are computed
+        assert_eq!(board.square_overlay(e4), SquareOverlay { attackers: vec![e7], defenders: vec![], malformed: vec![] });
+        assert_eq!(board.square_overlay(f4).defenders, vec![e2]);
+    }
+
+    #[test]
+    fn test_diagram_round_trip() {
+        let pasted = "
+            . . . . k . . .
+            . . . . r . . .
+            . . n . . . . .
+            . . . . . . . .
+            . . . N . . . .
+            . . P . . . . .
+            . . . . N . . .
+            . . . . K . . .
+            Black to move
+        ";
+        let board = Board::from_diagram(pasted).unwrap();
+        assert_eq!(board.to_fen(), "4k3/4r3/2n5/8/3N4/2P5/4N3/4K3 b - - 0 1");
+        assert_eq!(board.pinned_pieces(), vec![e2]);
+
+        // every kind of diagram this module draws reads back to the same position
+        for options in [
+            DiagramOptions::ascii().with_focus(d4).with_pins(),
+            DiagramOptions::unicode(),
+            DiagramOptions::unicode().with_colour().with_focus(d4).with_pins(),
+        ] {
+            let again = Board::from_diagram(&format!("{}\nb", board.diagram(&options))).unwrap();
+            assert!(board.diff(&again).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_malformed_diagrams() {
+        let line = |text: &str| match Board::from_diagram(text) {
+            Err(Error::InvalidDiagram { line, .. }) => line,
+            other => panic!("expected an invalid diagram, got {other:?}"),
+        };
+        let rows = ["....k...", "........", "........", "........", "........", "........", "........", "....K..."];
+        assert!(Board::from_diagram(&rows.join("\n")).is_ok());
+        assert_eq!(line(&rows[..7].join("\n")), 0);
+        assert_eq!(Board::from_diagram(&rows[..7].join("\n")).unwrap_err().to_string(), "diagram: expected 8 ranks, found 7");
+        assert_eq!(line(&format!("{}\n........", rows.join("\n"))), 9);
+        assert_eq!(line(&rows.join("\n").replacen("....K...", "....K..x", 1)), 8);
+        assert_eq!(line(&rows.join("\n").replacen("........", ".......", 1)), 2);
+        assert_eq!(line(&format!("7{}", rows.join("\n"))), 1);
+        assert!(matches!(Board::from_diagram(&rows.join("\n").replace('k', ".")), Err(Error::IllegalPosition(_))));
+    }
+}
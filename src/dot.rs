@@ -0,0 +1,156 @@
+// src/dot.rs
+// The exchangers as a Graphviz graph: one node per pid and an edge from every
+// piece in a chain to the piece the chain belongs to, labelled with the direction
+// and the position in the chain. The diff mode draws two boards' graphs together,
+// marking the edges a move added or removed.
+use crate::board::{Board, Square};
+use crate::compass_groups::Direction;
+use crate::pid::split_chain;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExchangerEdge {
+    /// The pid of the piece in the chain, or '?' and the raw chain when it cannot be read.
+    pub from: String,
+    /// The pid of the piece holding the chain.
+    pub to: String,
+    pub direction: Direction,
+    /// 1 for the first piece of the chain, 2 for the one behind it, and so on; 0 for
+    /// a chain that cannot be read.
+    pub position: usize,
+    /// '<' or '>' for the pin chains kept on kings.
+    pub marker: Option<char>,
+}
+
+impl ExchangerEdge {
+    fn label(&self) -> String {
+        if self.position == 0 {
+            return format!("{} ?", self.direction);
+        }
+        format!("{} {}{}", self.direction, self.marker.map(String::from).unwrap_or_default(), self.position)
+    }
+}
+
+impl Board {
+    /// Every exchanger relation, ordered by holder square then direction. A chain
+    /// that cannot be read becomes one edge from a '?' node naming the raw chain.
+    pub fn exchanger_edges(&self) -> Vec<ExchangerEdge> {
+        let mut holders: Vec<Square> = self.iter_pieces().map(|(square, _)| *square).collect();
+        holders.sort_by_key(|square| square.index());
+        let mut edges = Vec::new();
+        for holder in holders {
+            let Some(piece) = self.get_piece_on(holder) else { continue };
+            for direction in Direction::iter() {
+                let Some(chain) = piece.exchangers.get(&direction) else { continue };
+                let Ok((marker, pids)) = split_chain(chain) else {
+                    edges.push(ExchangerEdge {
+                        from: format!("?{chain}"),
+                        to: piece.get_pid().to_string(),
+                        direction,
+                        position: 0,
+                        marker: None,
+                    });
+                    continue;
+                };
+                for (i, pid) in pids.iter().enumerate() {
+                    edges.push(ExchangerEdge {
+                        from: pid.to_string(),
+                        to: piece.get_pid().to_string(),
+                        direction,
+                        position: i + 1,
+                        marker,
+                    });
+                }
+            }
+        }
+        edges
+    }
+
+    pub fn to_dot(&self) -> String {
+        let edges = self.exchanger_edges();
+        let mut out = graph_header(&pids_of(self));
+        for edge in &edges {
+            writeln!(out, "  {:?} -> {:?} [label={:?}];", edge.from, edge.to, edge.label()).unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn pids_of(board: &Board) -> Vec<String> {
+    let mut pieces: Vec<(usize, String)> =
+        board.iter_pieces().map(|(square, piece)| (square.index(), piece.get_pid().to_string())).collect();
+    pieces.sort();
+    pieces.into_iter().map(|(_, pid)| pid).collect()
+}
+
+fn graph_header(pids: &[String]) -> String {
+    let mut out = String::from("digraph exchangers {\n  node [shape=box, fontname=monospace];\n");
+    for pid in pids {
+        let style = if pid.ends_with(|c: char| c.is_ascii_uppercase()) { "" } else { ", style=filled, fillcolor=gray80" };
+        writeln!(out, "  {pid:?} [label={pid:?}{style}];").unwrap();
+    }
+    out
+}
+
+/// Both boards' exchanger graphs in one picture: edges only in `after` are green,
+/// edges only in `before` are red and dashed, shared ones grey. Pieces that moved
+/// show up as two nodes, since a pid includes its square.
+pub fn exchanger_dot_diff(before: &Board, after: &Board) -> String {
+    let old: BTreeSet<ExchangerEdge> = before.exchanger_edges().into_iter().collect();
+    let new: BTreeSet<ExchangerEdge> = after.exchanger_edges().into_iter().collect();
+    let mut pids: Vec<String> = pids_of(before);
+    for pid in pids_of(after) {
+        if !pids.contains(&pid) {
+            pids.push(pid);
+        }
+    }
+
+    let mut out = graph_header(&pids);
+    for edge in old.union(&new) {
+        let style = match (old.contains(edge), new.contains(edge)) {
+            (true, true) => "color=gray60, fontcolor=gray60",
+            (false, true) => "color=green4, fontcolor=green4, penwidth=2",
+            _ => "color=red3, fontcolor=red3, style=dashed, penwidth=2",
+        };
+        writeln!(out, "  {:?} -> {:?} [label={:?}, {style}];", edge.from, edge.to, edge.label()).unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_graph_and_diff() {
+        let board = Board::from_fen("k3q3/4r3/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+        let edges = board.exchanger_edges();
+        let edge = |from: &str, to: &str, direction, position, marker| ExchangerEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            direction,
+            position,
+            marker,
+        };
+        assert!(edges.contains(&edge("e7r", "e2N", Direction::N, 1, None)));
+        assert!(edges.contains(&edge("e8q", "e2N", Direction::N, 2, None)));
+        assert!(edges.contains(&edge("e7r", "e1K", Direction::N, 2, Some('<'))));
+
+        let dot = board.to_dot();
+        assert!(dot.starts_with("digraph exchangers {\n"));
+        assert!(dot.contains(r#"  "e8q" -> "e2N" [label="N 2"];"#));
+        assert!(dot.contains(r#"  "e2N" -> "e1K" [label="N <1"];"#));
+
+        // the king steps aside: the pin goes, and the knight's defender moves from e1 to d1
+        let after = board.play_san("Kd1").unwrap();
+        let diff = exchanger_dot_diff(&board, &after);
+        assert!(diff.contains(r#"  "e7r" -> "e1K" [label="N <2", color=red3"#));
+        assert!(diff.contains(r#"  "e1K" -> "e2N" [label="S 1", color=red3"#));
+        assert!(diff.contains(r#"  "d1K" -> "e2N" [label="SW 1", color=green4"#));
+        assert!(diff.contains(r#"  "e7r" -> "e2N" [label="N 1", color=gray60"#));
+    }
+}
@@ -0,0 +1,269 @@
+// src/epd.rs
+// Extended Position Description records and a test-suite runner over them. A
+// record is the first four FEN fields followed by `opcode operands;` pairs, e.g.
+//   r1b1k2r/.../R3K2R w KQkq - bm Qxf7+; id "WAC.042";
+use crate::board::Board;
+use crate::error::{Error, Result};
+use crate::moves::Move;
+use crate::search::{search, SearchLimits};
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct EpdRecord {
+    pub board: Board,
+    /// Operations in file order, each with its operands (quotes removed).
+    pub operations: Vec<(String, Vec<String>)>,
+}
+
+impl EpdRecord {
+    pub fn operands(&self, opcode: &str) -> Option<&[String]> {
+        self.operations.iter().find(|(op, _)| op == opcode).map(|(_, operands)| operands.as_slice())
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.operands("id").and_then(|ops| ops.first()).map(String::as_str)
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.operands("c0").and_then(|ops| ops.first()).map(String::as_str)
+    }
+
+    /// The `bm` moves resolved against the position.
+    pub fn best_moves(&self) -> Result<Vec<Move>> {
+        self.resolve("bm")
+    }
+
+    /// The `am` moves resolved against the position.
+    pub fn avoid_moves(&self) -> Result<Vec<Move>> {
+        self.resolve("am")
+    }
+
+    fn resolve(&self, opcode: &str) -> Result<Vec<Move>> {
+        self.operands(opcode).unwrap_or_default().iter().map(|san| self.board.parse_san(san)).collect()
+    }
+}
+
+/// Splits the operation text into `opcode operand...` groups at unquoted semicolons.
+fn split_operations(text: &str) -> Option<Vec<(String, Vec<String>)>> {
+    let mut operations = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                let mut group = std::mem::take(&mut words).into_iter();
+                operations.push((group.next()?, group.collect()));
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    // every operation is terminated, so only whitespace may follow the last ';'
+    if quoted || !word.is_empty() || !words.is_empty() {
+        return None;
+    }
+    Some(operations)
+}
+
+pub fn parse_epd_line(line: &str) -> Result<EpdRecord> {
+    let invalid = |reason: String| Error::InvalidEpd { record: line.to_string(), reason };
+    // the position fields may be separated by any run of whitespace; the rest of
+    // the line is left as it is for the operations, whose strings may hold spaces
+    let mut rest = line.trim();
+    let mut position: Vec<&str> = Vec::new();
+    while position.len() < 4 && !rest.is_empty() {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        position.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    if position.len() != 4 {
+        return Err(invalid("expected the four FEN position fields".to_string()));
+    }
+    let operations = split_operations(rest)
+        .ok_or_else(|| invalid("operations must each end with ';'".to_string()))?;
+
+    let op = |name: &str| operations.iter().find(|(op, _)| op == name).and_then(|(_, ops)| ops.first().cloned());
+    let halfmove = op("hmvc").unwrap_or_else(|| "0".to_string());
+    let fullmove = op("fmvn").unwrap_or_else(|| "1".to_string());
+    let board = Board::from_fen(&format!("{} {halfmove} {fullmove}", position.join(" ")))
+        .map_err(|err| invalid(err.to_string()))?;
+    Ok(EpdRecord { board, operations })
+}
+
+/// Reads a whole EPD file, one record per non-blank line.
+pub fn parse_epd(text: &str) -> Result<Vec<EpdRecord>> {
+    text.lines().filter(|line| !line.trim().is_empty()).map(parse_epd_line).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct EpdOutcome {
+    pub id: String,
+    /// The `bm` and `am` operands as written in the file.
+    pub expected: String,
+    pub found: Option<String>,
+    pub solved: bool,
+    /// Why the record could not be searched, e.g. a `bm` move that is not legal.
+    pub error: Option<String>,
+    pub score: i32,
+    pub depth: u32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SuiteReport {
+    pub outcomes: Vec<EpdOutcome>,
+}
+
+impl SuiteReport {
+    pub fn solved(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.solved).count()
+    }
+
+    pub fn unsolved(&self) -> usize {
+        self.outcomes.len() - self.solved()
+    }
+}
+
+impl fmt::Display for SuiteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<16} {:<20} {:<10} {:>7} {:>5} {:>10} {:>8}  result", "id", "expected", "found", "score", "depth", "nodes", "ms")?;
+        for o in &self.outcomes {
+            writeln!(
+                f,
+                "{:<16} {:<20} {:<10} {:>7} {:>5} {:>10} {:>8}  {}",
+                o.id,
+                o.expected,
+                o.found.as_deref().unwrap_or("-"),
+                o.score,
+                o.depth,
+                o.nodes,
+                o.elapsed.as_millis(),
+                match (&o.error, o.solved) {
+                    (Some(err), _) => format!("UNSOLVED ({err})"),
+                    (None, true) => "solved".to_string(),
+                    (None, false) => "UNSOLVED".to_string(),
+                }
+            )?;
+        }
+        write!(f, "solved {} of {} ({} unsolved)", self.solved(), self.outcomes.len(), self.unsolved())
+    }
+}
+
+/// Searches one record. It counts as solved when the search picks one of the `bm`
+/// moves, if any are given, and none of the `am` moves. A record whose `bm` or `am`
+/// moves do not resolve is reported unsolved with the reason, without a search.
+pub fn run_record(record: &EpdRecord, index: usize, limits: SearchLimits) -> EpdOutcome {
+    let expected: Vec<String> = ["bm", "am"]
+        .into_iter()
+        .filter_map(|opcode| record.operands(opcode).map(|operands| format!("{opcode} {}", operands.join(" "))))
+        .collect();
+    let mut outcome = EpdOutcome {
+        id: record.id().map_or_else(|| format!("#{}", index + 1), str::to_string),
+        expected: expected.join("; "),
+        found: None,
+        solved: false,
+        error: None,
+        score: 0,
+        depth: 0,
+        nodes: 0,
+        elapsed: Duration::ZERO,
+    };
+    let (best, avoid) = match record.best_moves().and_then(|best| Ok((best, record.avoid_moves()?))) {
+        Ok(moves) => moves,
+        Err(err) => {
+            outcome.error = Some(err.to_string());
+            return outcome;
+        }
+    };
+
+    let result = search(&record.board, limits);
+    outcome.solved = result.best_move.is_some_and(|mv| (best.is_empty() || best.contains(&mv)) && !avoid.contains(&mv));
+    outcome.found = result.best_move.and_then(|mv| record.board.to_san(mv).ok());
+    outcome.score = result.score;
+    outcome.depth = result.depth;
+    outcome.nodes = result.nodes;
+    outcome.elapsed = result.elapsed;
+    outcome
+}
+
+/// Runs every record; one that cannot be searched is reported rather than ending the suite.
+pub fn run_suite(records: &[EpdRecord], limits: SearchLimits) -> SuiteReport {
+    let outcomes = records
+        .iter()
+        .enumerate()
+        .map(|(index, record)| run_record(record, index, limits))
+        .collect();
+    SuiteReport { outcomes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square::*;
+
+    const SUITE: &str = r#"
+6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id "back rank"; c0 "mate in one";
+4k3/8/8/3q4/8/8/8/3RK3 w - - bm Rxd5; id "free queen";
+4k3/8/8/3q4/8/8/8/3RK3 w - - am Rxd5; id "avoid";
+"#;
+
+    #[test]
+    fn test_parse_epd_operations() {
+        let records = parse_epd(SUITE).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].id(), Some("back rank"));
+        assert_eq!(records[0].comment(), Some("mate in one"));
+        assert_eq!(records[0].best_moves().unwrap(), vec![Move::new(a1, a8)]);
+        assert_eq!(records[2].avoid_moves().unwrap(), vec![Move::new(d1, d5)]);
+
+        let clocks = parse_epd_line("4k3/8/8/8/8/8/8/4K3 b - - hmvc 12; fmvn 40;").unwrap();
+        assert_eq!(clocks.board.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 12 40");
+
+        let spaced = parse_epd_line("4k3/8/8/8/8/8/8/4K3  b\t-   - hmvc 12;").unwrap();
+        assert_eq!(spaced.board.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 12 1");
+        assert!(matches!(parse_epd_line("4k3/8/8/8 w"), Err(Error::InvalidEpd { .. })));
+        assert!(matches!(parse_epd_line("4k3/8/8/8/8/8/8/4K3 w - - bm Kd1"), Err(Error::InvalidEpd { .. })));
+        assert!(matches!(parse_epd_line("4k3/8/8/8/8/8/8/4K3 w - - id \"open;"), Err(Error::InvalidEpd { .. })));
+    }
+
+    #[test]
+    fn test_run_suite_reports_each_position() {
+        let records = parse_epd(SUITE).unwrap();
+        let report = run_suite(&records, SearchLimits::depth(2));
+        let solved: Vec<bool> = report.outcomes.iter().map(|o| o.solved).collect();
+        assert_eq!(solved, vec![true, true, false]);
+        assert_eq!(report.outcomes[0].found.as_deref(), Some("Ra8#"));
+        assert_eq!(report.outcomes[2].expected, "am Rxd5");
+
+        let table = report.to_string();
+        assert!(table.lines().nth(1).unwrap().starts_with("back rank"));
+        assert!(table.ends_with("solved 2 of 3 (1 unsolved)"));
+    }
+
+    #[test]
+    fn test_run_suite_reports_unresolved_moves() {
+        let records = parse_epd(&format!("4k3/8/8/8/8/8/8/4K3 w - - bm Qh5; id \"no queen\";\n{SUITE}")).unwrap();
+        let report = run_suite(&records, SearchLimits::depth(2));
+        assert_eq!(report.outcomes.len(), 4);
+        let broken = &report.outcomes[0];
+        assert!(!broken.solved && broken.found.is_none());
+        assert!(broken.error.is_some());
+        assert_eq!(broken.expected, "bm Qh5");
+        assert!(report.outcomes[1].solved && report.outcomes[1].error.is_none());
+
+        let table = report.to_string();
+        assert!(table.lines().nth(1).unwrap().contains("UNSOLVED ("));
+        assert!(table.ends_with("solved 2 of 4 (2 unsolved)"));
+    }
+}
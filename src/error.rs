@@ -0,0 +1,84 @@
+use std::fmt;
+use crate::board::Square;
+use crate::game::Termination;
+use crate::legality::Violation;
+use crate::pieces::Side;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// A piece identifier that is not a file, a rank and a piece letter, e.g. "e1K".
+    InvalidPid(String),
+    /// A square expected to hold a piece was empty.
+    EmptySquare(Square),
+    IllegalMove { from: Square, to: Square, reason: String },
+    /// The exchanger strings refer to pieces or squares that do not agree with the board.
+    InconsistentExchangers(String),
+    /// Every broken invariant found by `Board::validate`.
+    CorruptBoard(Vec<String>),
+    InvalidFen { fen: String, reason: String },
+    /// A SAN or UCI move that is malformed, ambiguous or not legal in the position.
+    InvalidNotation { notation: String, reason: String },
+    /// PGN text that cannot be read, or a mainline move that cannot be replayed; line
+    /// 0 stands for a game written from a board rather than read from text.
+    InvalidPgn { line: usize, reason: String },
+    /// An EPD record whose position or operations cannot be read.
+    InvalidEpd { record: String, reason: String },
+    /// Every reason a set-up position could not arise in a game.
+    IllegalPosition(Vec<Violation>),
+    /// A game tree node id that was never issued or whose subtree was deleted.
+    UnknownNode(usize),
+    /// An attempt to delete the root of a game tree, which holds the start position.
+    CannotDeleteRoot,
+    /// A side tried to move when it was the other side's turn.
+    OutOfTurn(Side),
+    /// An action on a game that has already ended.
+    GameOver(Termination),
+    /// A side answered a draw offer its opponent had not made.
+    NoDrawOffer(Side),
+    /// A shell command that is unknown or has the wrong arguments.
+    InvalidCommand { command: String, reason: String },
+    /// A scenario file that cannot be read; line 0 stands for the file as a whole.
+    InvalidScenario { line: usize, reason: String },
+    /// A board diagram that cannot be read; line 0 stands for the diagram as a whole.
+    InvalidDiagram { line: usize, reason: String },
+    /// A name missing from the named position library.
+    UnknownPosition(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidPid(pid) => {
+                write!(f, "'{pid}' is not a valid pid (expected file, rank and piece letter, e.g. 'e1K')")
+            }
+            Error::EmptySquare(square) => write!(f, "no piece on {square}"),
+            Error::IllegalMove { from, to, reason } => write!(f, "illegal move {from}-{to}: {reason}"),
+            Error::InconsistentExchangers(detail) => write!(f, "inconsistent exchanger state: {detail}"),
+            Error::CorruptBoard(violations) => write!(f, "board invariants violated: {}", violations.join("; ")),
+            Error::InvalidFen { fen, reason } => write!(f, "invalid FEN '{fen}': {reason}"),
+            Error::InvalidNotation { notation, reason } => write!(f, "cannot read move '{notation}': {reason}"),
+            Error::InvalidPgn { line: 0, reason } => write!(f, "PGN: {reason}"),
+            Error::InvalidPgn { line, reason } => write!(f, "PGN line {line}: {reason}"),
+            Error::InvalidEpd { record, reason } => write!(f, "invalid EPD '{record}': {reason}"),
+            Error::IllegalPosition(violations) => {
+                let reasons: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+                write!(f, "illegal position: {}", reasons.join("; "))
+            }
+            Error::UnknownNode(id) => write!(f, "no game tree node {id}"),
+            Error::CannotDeleteRoot => write!(f, "the root of a game tree cannot be deleted"),
+            Error::OutOfTurn(side) => write!(f, "it is not {side:?}'s turn"),
+            Error::GameOver(termination) => write!(f, "the game is over ({termination})"),
+            Error::NoDrawOffer(side) => write!(f, "{side:?} has no draw offer to answer"),
+            Error::InvalidCommand { command, reason } => write!(f, "'{command}': {reason}"),
+            Error::InvalidScenario { line: 0, reason } => write!(f, "scenario: {reason}"),
+            Error::InvalidScenario { line, reason } => write!(f, "scenario line {line}: {reason}"),
+            Error::InvalidDiagram { line: 0, reason } => write!(f, "diagram: {reason}"),
+            Error::InvalidDiagram { line, reason } => write!(f, "diagram line {line}: {reason}"),
+            Error::UnknownPosition(name) => write!(f, "no named position '{name}'"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,60 @@
+// src/eval.rs
+// Static evaluation: material plus a small positional nudge. Scores are in
+// centipawns from the point of view of the side to move.
+use crate::board::{Board, Square};
+use crate::pieces::Side;
+
+/// Larger than any material score; a mate found `n` plies away scores `MATE - n`.
+pub const MATE: i32 = 100_000;
+
+pub fn piece_value(pchar: char) -> i32 {
+    match pchar.to_ascii_uppercase() {
+        'P' => 100,
+        'N' => 320,
+        'B' => 330,
+        'R' => 500,
+        'Q' => 900,
+        _ => 0,
+    }
+}
+
+/// Pawns gain for every rank advanced; knights and bishops for closeness to the centre.
+fn placement_bonus(pchar: char, square: Square, side: Side) -> i32 {
+    let rank = square.rank().index() as i32;
+    let advanced = if side == Side::White { rank - 1 } else { 6 - rank };
+    let file = square.file().index() as i32;
+    let centre_distance = (2 * file - 7).abs().max((2 * rank - 7).abs());
+    match pchar.to_ascii_uppercase() {
+        'P' => 5 * advanced,
+        'N' | 'B' => 10 - 3 * centre_distance / 2,
+        _ => 0,
+    }
+}
+
+pub fn evaluate(board: &Board) -> i32 {
+    let mut score = 0;
+    for (square, piece) in board.iter_pieces() {
+        let pchar = piece.get_piece_type_as_char();
+        let side = piece.get_side();
+        let value = piece_value(pchar) + placement_bonus(pchar, *square, side);
+        score += if side == board.turn() { value } else { -value };
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluation_is_symmetric_and_counts_material() {
+        let start = Board::from_fen(crate::fen::STANDARD_FEN).unwrap();
+        assert_eq!(evaluate(&start), 0);
+
+        let up_a_rook = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(evaluate(&up_a_rook), 500);
+        let mut black_to_move = up_a_rook.clone();
+        black_to_move.set_turn(Side::Black);
+        assert_eq!(evaluate(&black_to_move), -500);
+    }
+}
@@ -0,0 +1,156 @@
+// src/fen.rs
+// Forsyth-Edwards Notation in and out. Loaded positions go through the legality
+// checker, so a FEN that parses but could not arise in a game is still rejected.
+use crate::board::{Board, CastlingRights, File, Rank, Square};
+use crate::error::{Error, Result};
+use crate::pieces::Side;
+use std::str::FromStr;
+
+pub const STANDARD_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+fn invalid(fen: &str, reason: impl Into<String>) -> Error {
+    Error::InvalidFen { fen: fen.to_string(), reason: reason.into() }
+}
+
+impl Board {
+    pub fn from_fen(fen: &str) -> Result<Board> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(invalid(fen, format!("expected 4 to 6 fields, found {}", fields.len())));
+        }
+
+        let mut board = Board::new();
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(invalid(fen, format!("expected 8 ranks, found {}", ranks.len())));
+        }
+        for (row, rank_str) in ranks.iter().enumerate() {
+            let rank = Rank::from_index(7 - row).expect("eight ranks");
+            let mut file_idx = 0usize;
+            let mut after_digit = false;
+            for c in rank_str.chars() {
+                if c.is_ascii_digit() {
+                    if !('1'..='8').contains(&c) || after_digit {
+                        return Err(invalid(fen, format!("rank {} has a malformed empty-square count", rank.to_char())));
+                    }
+                    file_idx += c.to_digit(10).expect("a digit") as usize;
+                    after_digit = true;
+                    continue;
+                }
+                after_digit = false;
+                let file = File::from_index(file_idx)
+                    .ok_or_else(|| invalid(fen, format!("rank {} is longer than 8 squares", rank.to_char())))?;
+                let square = Square::from_file_rank(file, rank);
+                board
+                    .create_and_place_piece(&format!("{square}{c}"))
+                    .map_err(|_| invalid(fen, format!("'{c}' is not a piece letter")))?;
+                file_idx += 1;
+            }
+            if file_idx != 8 {
+                return Err(invalid(fen, format!("rank {} covers {file_idx} squares, not 8", rank.to_char())));
+            }
+        }
+
+        board.set_turn(match fields[1] {
+            "w" => Side::White,
+            "b" => Side::Black,
+            other => return Err(invalid(fen, format!("side to move '{other}' is not 'w' or 'b'"))),
+        });
+        let castling = CastlingRights::from_fen(fields[2])
+            .ok_or_else(|| invalid(fen, format!("castling field '{}' is malformed", fields[2])))?;
+        board.set_castling_rights(castling);
+        let en_passant = match fields[3] {
+            "-" => None,
+            sq => Some(Square::from_str(sq).map_err(|_| invalid(fen, format!("'{sq}' is not a square")))?),
+        };
+        board.set_en_passant_square(en_passant);
+
+        let clock = |idx: usize, default: u32| -> Result<u32> {
+            fields.get(idx).map_or(Ok(default), |field| {
+                field.parse().map_err(|_| invalid(fen, format!("'{field}' is not a move count")))
+            })
+        };
+        board.set_move_clocks(clock(4, 0)?, clock(5, 1)?);
+
+        board.check_legality()?;
+        board.build_all_xchngrs()?;
+        Ok(board)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for rank_idx in (0..8).rev() {
+            let mut empty = 0;
+            for file_idx in 0..8 {
+                let square = Square::from_index(rank_idx * 8 + file_idx).expect("on the board");
+                match self.get_piece_on(square) {
+                    None => empty += 1,
+                    Some(piece) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(piece.get_piece_type_as_char());
+                    }
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank_idx > 0 {
+                placement.push('/');
+            }
+        }
+
+        let side = if self.turn() == Side::White { "w" } else { "b" };
+        let en_passant = self.en_passant_square().map_or("-".to_string(), |sq| sq.to_string());
+        format!(
+            "{placement} {side} {} {en_passant} {} {}",
+            self.castling_rights().to_fen(),
+            self.halfmove_clock(),
+            self.fullmove_number()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::legality::Violation;
+
+    #[test]
+    fn test_fen_round_trip() {
+        let board = Board::from_fen(STANDARD_FEN).unwrap();
+        assert_eq!(board.len(), 32);
+        assert_eq!(board.castling_rights(), CastlingRights::ALL);
+        assert_eq!(board.to_fen(), STANDARD_FEN);
+
+        let fen = "4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 42";
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.en_passant_square(), Some(Square::e6));
+        assert_eq!(board.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_fen_rejects_malformed_and_illegal_input() {
+        assert!(matches!(Board::from_fen("8/8/8 w - -"), Err(Error::InvalidFen { .. })));
+        assert!(matches!(Board::from_fen("4k3/8/8/8/8/8/8/4K4 w - -"), Err(Error::InvalidFen { .. })));
+        assert!(matches!(Board::from_fen("4k3/8/8/8/8/8/8/4X3 w - -"), Err(Error::InvalidFen { .. })));
+        assert!(matches!(Board::from_fen("4k3/08/8/8/8/8/8/4K3 w - -"), Err(Error::InvalidFen { .. })));
+        assert!(matches!(Board::from_fen("4k3/44/8/8/8/8/8/4K3 w - -"), Err(Error::InvalidFen { .. })));
+        assert!(matches!(Board::from_fen("4k3/8/8/8/8/8/8/4K3 x - -"), Err(Error::InvalidFen { .. })));
+
+        let err = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w KQ e3").unwrap_err();
+        assert_eq!(
+            err,
+            Error::IllegalPosition(vec![
+                Violation::EnPassant {
+                    square: Square::e3,
+                    reason: "must be on rank 6 with White to move".to_string()
+                },
+                Violation::Castling { right: 'K', reason: "rook is not on h1".to_string() },
+                Violation::Castling { right: 'Q', reason: "rook is not on a1".to_string() },
+            ])
+        );
+    }
+}
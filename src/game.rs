@@ -0,0 +1,376 @@
+// src/game.rs
+// A game in progress: the board, the history of positions, both clocks and the
+// outcome once there is one. Callers say who is moving and how long they took;
+// the game flips turns, charges the clocks and decides when it is over.
+use crate::board::Board;
+use crate::error::{Error, Result};
+use crate::moves::Move;
+use crate::pgn::{GameResult, PgnGame};
+use crate::pieces::Side;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    pub base: Duration,
+    /// Added to a side's clock after each of its moves.
+    pub increment: Duration,
+    /// When set, `base` is added again every time a side completes this many moves.
+    pub moves_per_session: Option<u32>,
+}
+
+impl TimeControl {
+    pub fn new(base: Duration, increment: Duration) -> Self {
+        TimeControl { base, increment, moves_per_session: None }
+    }
+
+    pub fn with_moves_per_session(mut self, moves: u32) -> Self {
+        self.moves_per_session = Some(moves);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    ThreefoldRepetition,
+    /// A hundred half-moves without a capture or pawn move.
+    FiftyMoveRule,
+    InsufficientMaterial,
+    /// Includes the drawn case where the flag falls but the opponent cannot mate.
+    TimeForfeit,
+    Resignation,
+    DrawAgreement,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::ThreefoldRepetition => "threefold repetition",
+            Termination::FiftyMoveRule => "fifty-move rule",
+            Termination::InsufficientMaterial => "insufficient material",
+            Termination::TimeForfeit => "time forfeit",
+            Termination::Resignation => "resignation",
+            Termination::DrawAgreement => "draw agreement",
+        };
+        write!(f, "{text}")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    start: Board,
+    board: Board,
+    /// `Board::position_key` of every position so far, the start included.
+    keys: Vec<u64>,
+    time_control: Option<TimeControl>,
+    /// Time left for White and Black; unused without a time control.
+    clocks: [Duration; 2],
+    draw_offer: Option<Side>,
+    outcome: Option<(GameResult, Termination)>,
+}
+
+fn clock_index(side: Side) -> usize {
+    match side {
+        Side::White => 0,
+        Side::Black => 1,
+    }
+}
+
+fn win_for(side: Side) -> GameResult {
+    match side {
+        Side::White => GameResult::WhiteWins,
+        Side::Black => GameResult::BlackWins,
+    }
+}
+
+/// A lone king, or a king with a single knight or bishop, cannot force mate. This is
+/// the usual approximation of the rule rather than a search for helpmates.
+fn has_mating_material(board: &Board, side: Side) -> bool {
+    let mut minors = 0;
+    for (_, piece) in board.iter_pieces().filter(|(_, p)| p.get_side() == side) {
+        match piece.get_piece_type_as_char().to_ascii_uppercase() {
+            'K' => {}
+            'N' | 'B' => minors += 1,
+            _ => return true,
+        }
+    }
+    minors > 1
+}
+
+impl Game {
+    /// An untimed game from `start`.
+    pub fn new(start: Board) -> Self {
+        let key = start.position_key();
+        Game {
+            board: start.clone(),
+            start,
+            keys: vec![key],
+            time_control: None,
+            clocks: [Duration::ZERO; 2],
+            draw_offer: None,
+            outcome: None,
+        }
+    }
+
+    pub fn with_time_control(start: Board, time_control: TimeControl) -> Self {
+        let mut game = Game::new(start);
+        game.time_control = Some(time_control);
+        game.clocks = [time_control.base; 2];
+        game
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    /// The moves played since the start position.
+    pub fn moves(&self) -> &[Move] {
+        &self.board.moves()[self.start.moves().len()..]
+    }
+
+    pub fn turn(&self) -> Side {
+        self.board.turn()
+    }
+
+    pub fn time_control(&self) -> Option<TimeControl> {
+        self.time_control
+    }
+
+    /// Time left on `side`'s clock, or `None` in an untimed game.
+    pub fn remaining(&self, side: Side) -> Option<Duration> {
+        self.time_control.map(|_| self.clocks[clock_index(side)])
+    }
+
+    pub fn draw_offer(&self) -> Option<Side> {
+        self.draw_offer
+    }
+
+    pub fn result(&self) -> GameResult {
+        self.outcome.map_or(GameResult::Ongoing, |(result, _)| result)
+    }
+
+    pub fn termination(&self) -> Option<Termination> {
+        self.outcome.map(|(_, termination)| termination)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    fn ensure_ongoing(&self) -> Result<()> {
+        match self.outcome {
+            Some((_, termination)) => Err(Error::GameOver(termination)),
+            None => Ok(()),
+        }
+    }
+
+    /// Charges `elapsed` to the side to move. When the flag falls the game ends on
+    /// time, and true is returned.
+    pub fn tick(&mut self, elapsed: Duration) -> Result<bool> {
+        self.ensure_ongoing()?;
+        if self.time_control.is_none() {
+            return Ok(false);
+        }
+        let side = self.turn();
+        let clock = &mut self.clocks[clock_index(side)];
+        if elapsed < *clock {
+            *clock -= elapsed;
+            return Ok(false);
+        }
+        *clock = Duration::ZERO;
+        let result = if has_mating_material(&self.board, side.opposite()) { win_for(side.opposite()) } else { GameResult::Draw };
+        self.outcome = Some((result, Termination::TimeForfeit));
+        Ok(true)
+    }
+
+    fn ensure_turn(&self, side: Side) -> Result<()> {
+        self.ensure_ongoing()?;
+        if side != self.turn() {
+            return Err(Error::OutOfTurn(side));
+        }
+        Ok(())
+    }
+
+    /// Plays `side`'s move, which took `elapsed` off its clock. A move made after the
+    /// flag fell is not played; the game ends on time instead.
+    pub fn play(&mut self, side: Side, mv: Move, elapsed: Duration) -> Result<()> {
+        self.ensure_turn(side)?;
+        let next = self.board.make_move(mv)?;
+        if self.tick(elapsed)? {
+            return Ok(());
+        }
+        if let Some(control) = self.time_control {
+            let clock = &mut self.clocks[clock_index(side)];
+            *clock += control.increment;
+            let moves_made = self.moves().iter().skip(usize::from(self.start.turn() != side)).step_by(2).count() as u32 + 1;
+            if control.moves_per_session.is_some_and(|session| moves_made.is_multiple_of(session)) {
+                self.clocks[clock_index(side)] += control.base;
+            }
+        }
+        self.board = next;
+        self.keys.push(self.board.position_key());
+        // making a move declines the opponent's offer
+        if self.draw_offer == Some(side.opposite()) {
+            self.draw_offer = None;
+        }
+        self.outcome = self.adjudicate();
+        Ok(())
+    }
+
+    pub fn play_san(&mut self, side: Side, san: &str, elapsed: Duration) -> Result<()> {
+        self.ensure_turn(side)?;
+        let mv = self.board.parse_san(san)?;
+        self.play(side, mv, elapsed)
+    }
+
+    fn adjudicate(&self) -> Option<(GameResult, Termination)> {
+        let board = &self.board;
+        if board.is_checkmate() {
+            return Some((win_for(board.turn().opposite()), Termination::Checkmate));
+        }
+        if board.is_stalemate() {
+            return Some((GameResult::Draw, Termination::Stalemate));
+        }
+        if !has_mating_material(board, Side::White) && !has_mating_material(board, Side::Black) {
+            return Some((GameResult::Draw, Termination::InsufficientMaterial));
+        }
+        let current = self.keys.last().copied();
+        if self.keys.iter().filter(|key| Some(**key) == current).count() >= 3 {
+            return Some((GameResult::Draw, Termination::ThreefoldRepetition));
+        }
+        if board.halfmove_clock() >= 100 {
+            return Some((GameResult::Draw, Termination::FiftyMoveRule));
+        }
+        None
+    }
+
+    pub fn resign(&mut self, side: Side) -> Result<()> {
+        self.ensure_ongoing()?;
+        self.outcome = Some((win_for(side.opposite()), Termination::Resignation));
+        Ok(())
+    }
+
+    /// Offers a draw. The offer stands until the opponent accepts it, declines it or
+    /// makes a move.
+    pub fn offer_draw(&mut self, side: Side) -> Result<()> {
+        self.ensure_ongoing()?;
+        self.draw_offer = Some(side);
+        Ok(())
+    }
+
+    pub fn accept_draw(&mut self, side: Side) -> Result<()> {
+        self.ensure_ongoing()?;
+        if self.draw_offer != Some(side.opposite()) {
+            return Err(Error::NoDrawOffer(side));
+        }
+        self.draw_offer = None;
+        self.outcome = Some((GameResult::Draw, Termination::DrawAgreement));
+        Ok(())
+    }
+
+    pub fn decline_draw(&mut self, side: Side) -> Result<()> {
+        self.ensure_ongoing()?;
+        if self.draw_offer != Some(side.opposite()) {
+            return Err(Error::NoDrawOffer(side));
+        }
+        self.draw_offer = None;
+        Ok(())
+    }
+
+    /// The game so far as PGN, with its result and a `Termination` tag once over.
+    pub fn to_pgn_game(&self) -> Result<PgnGame> {
+        let mut game = self.board.to_pgn_game(&self.start)?;
+        game.result = self.result();
+        if let Some(termination) = self.termination() {
+            game.tags.push(("Termination".to_string(), termination.to_string()));
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::STANDARD_FEN;
+
+    const NO_TIME: Duration = Duration::ZERO;
+
+    fn standard() -> Board {
+        Board::from_fen(STANDARD_FEN).unwrap()
+    }
+
+    fn play_all(game: &mut Game, sans: &[&str]) {
+        for san in sans {
+            game.play_san(game.turn(), san, NO_TIME).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_turn_order_and_checkmate() {
+        let mut game = Game::new(standard());
+        assert_eq!(game.play_san(Side::Black, "e5", NO_TIME), Err(Error::OutOfTurn(Side::Black)));
+        play_all(&mut game, &["f3", "e5", "g4", "Qh4#"]);
+        assert_eq!(game.result(), GameResult::BlackWins);
+        assert_eq!(game.termination(), Some(Termination::Checkmate));
+        assert_eq!(game.resign(Side::White), Err(Error::GameOver(Termination::Checkmate)));
+        let pgn = game.to_pgn_game().unwrap();
+        assert_eq!(pgn.tag("Termination"), Some("checkmate"));
+        assert_eq!(pgn.result, GameResult::BlackWins);
+    }
+
+    #[test]
+    fn test_draws_by_rule_and_agreement() {
+        let mut game = Game::new(standard());
+        play_all(&mut game, &["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"]);
+        assert!(!game.is_over());
+        play_all(&mut game, &["Ng8"]);
+        assert_eq!(game.termination(), Some(Termination::ThreefoldRepetition));
+
+        let mut bare = Game::new(Board::from_fen("4k3/8/8/8/8/8/3q4/4K3 w - - 0 1").unwrap());
+        play_all(&mut bare, &["Kxd2"]);
+        assert_eq!((bare.result(), bare.termination()), (GameResult::Draw, Some(Termination::InsufficientMaterial)));
+
+        let mut agreed = Game::new(standard());
+        agreed.offer_draw(Side::White).unwrap();
+        assert_eq!(agreed.accept_draw(Side::White), Err(Error::NoDrawOffer(Side::White)));
+        play_all(&mut agreed, &["e4"]);
+        assert_eq!(agreed.draw_offer(), Some(Side::White));
+        // Black moving instead of answering declines the offer
+        play_all(&mut agreed, &["e5"]);
+        assert_eq!(agreed.draw_offer(), None);
+        agreed.offer_draw(Side::Black).unwrap();
+        agreed.accept_draw(Side::White).unwrap();
+        assert_eq!(agreed.termination(), Some(Termination::DrawAgreement));
+    }
+
+    #[test]
+    fn test_clocks_increment_sessions_and_flag() {
+        let secs = Duration::from_secs;
+        let control = TimeControl::new(secs(60), secs(2)).with_moves_per_session(2);
+        let mut game = Game::with_time_control(standard(), control);
+        game.play_san(Side::White, "e4", secs(10)).unwrap();
+        assert_eq!(game.remaining(Side::White), Some(secs(52)));
+        game.play_san(Side::Black, "e5", secs(5)).unwrap();
+        // White's second move completes a session and earns another base period
+        game.play_san(Side::White, "Nf3", secs(10)).unwrap();
+        assert_eq!(game.remaining(Side::White), Some(secs(104)));
+        assert_eq!(game.remaining(Side::Black), Some(secs(57)));
+
+        game.play_san(Side::Black, "Nc6", secs(57)).unwrap();
+        assert_eq!((game.result(), game.termination()), (GameResult::WhiteWins, Some(Termination::TimeForfeit)));
+        assert_eq!(game.moves().len(), 3);
+
+        let mut lone_king = Game::with_time_control(Board::from_fen("4k3/8/8/8/8/8/3Q4/4K3 w - - 0 1").unwrap(), control);
+        assert!(lone_king.tick(secs(61)).unwrap());
+        assert_eq!(lone_king.result(), GameResult::Draw);
+        assert_eq!(Game::new(standard()).remaining(Side::White), None);
+    }
+}
@@ -0,0 +1,428 @@
+// src/game_tree.rs
+// A game as a tree of moves. Nodes live in an arena and refer to each other by
+// `NodeId`; a node's first child continues the mainline and the others are
+// variations. A cursor marks the current node for navigation.
+//
+// Nodes keep only their move and position key. The tree holds the start position
+// and the position at the cursor; moving the cursor anywhere but one step forward
+// replays the moves from the start on a board without exchangers and rebuilds them
+// once at the end.
+use crate::board::Board;
+use crate::error::{Error, Result};
+use crate::moves::Move;
+use crate::pgn::{Evaluation, GameResult, PgnGame, PgnMove};
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone)]
+pub struct GameNode {
+    /// The move leading here; `None` only for the root.
+    pub mv: Option<Move>,
+    pub san: String,
+    /// `Board::position_key` of the position after the move.
+    pub key: u64,
+    /// A comment that opens the line before this move, kept apart so it is written
+    /// back in front of the move.
+    pub comment_before: Option<String>,
+    pub comments: Vec<String>,
+    pub nags: Vec<u8>,
+    pub eval: Option<Evaluation>,
+    pub clock: Option<u32>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl GameNode {
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    /// Mainline continuation first, then the variations.
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GameTree {
+    nodes: Vec<Option<GameNode>>,
+    current: NodeId,
+    start: Board,
+    /// The position at `current`.
+    board: Board,
+}
+
+const ROOT: NodeId = 0;
+
+impl GameTree {
+    pub fn new(start: Board) -> Self {
+        let root = GameNode {
+            mv: None,
+            san: String::new(),
+            key: start.position_key(),
+            comment_before: None,
+            comments: Vec::new(),
+            nags: Vec::new(),
+            eval: None,
+            clock: None,
+            parent: None,
+            children: Vec::new(),
+        };
+        GameTree { nodes: vec![Some(root)], current: ROOT, board: start.clone(), start }
+    }
+
+    pub fn root(&self) -> NodeId {
+        ROOT
+    }
+
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    pub fn node(&self, id: NodeId) -> Result<&GameNode> {
+        self.nodes.get(id).and_then(Option::as_ref).ok_or(Error::UnknownNode(id))
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Result<&mut GameNode> {
+        self.nodes.get_mut(id).and_then(Option::as_mut).ok_or(Error::UnknownNode(id))
+    }
+
+    fn at(&self, id: NodeId) -> &GameNode {
+        self.nodes[id].as_ref().expect("live node id")
+    }
+
+    fn at_mut(&mut self, id: NodeId) -> &mut GameNode {
+        self.nodes[id].as_mut().expect("live node id")
+    }
+
+    /// The position at the cursor.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The position at `id`, replayed from the start.
+    pub fn board_at(&self, id: NodeId) -> Result<Board> {
+        let path = self.path_to(id)?;
+        if path.is_empty() {
+            return Ok(self.start.clone());
+        }
+        let mut board = self.start.without_exchangers();
+        for node in path {
+            board = board.play_unprocessed(self.at(node).mv.expect("path excludes the root"))?;
+        }
+        board.build_all_xchngrs()?;
+        Ok(board)
+    }
+
+    /// Moves the cursor to `id`, which must be live.
+    fn set_cursor(&mut self, id: NodeId) {
+        self.board = self.board_at(id).expect("every move in the tree was legal when it was added");
+        self.current = id;
+    }
+
+    /// Plays a move from the cursor and moves onto it. A move already in the tree is
+    /// reused; a new one becomes a variation, or the mainline if it is the first.
+    pub fn add_move(&mut self, mv: Move) -> Result<NodeId> {
+        let here = self.current;
+        if let Some(existing) = self.at(here).children.iter().copied().find(|child| self.at(*child).mv == Some(mv)) {
+            self.board = self.board.make_move(mv)?;
+            self.current = existing;
+            return Ok(existing);
+        }
+        let san = self.board.to_san(mv)?;
+        let next = self.board.make_move(mv)?;
+        let id = self.nodes.len();
+        self.nodes.push(Some(GameNode {
+            mv: Some(mv),
+            san,
+            key: next.position_key(),
+            comment_before: None,
+            comments: Vec::new(),
+            nags: Vec::new(),
+            eval: None,
+            clock: None,
+            parent: Some(here),
+            children: Vec::new(),
+        }));
+        self.at_mut(here).children.push(id);
+        self.board = next;
+        self.current = id;
+        Ok(id)
+    }
+
+    pub fn add_san(&mut self, san: &str) -> Result<NodeId> {
+        let mv = self.board().parse_san(san)?;
+        self.add_move(mv)
+    }
+
+    /// Follows the mainline one move; false at the end of the line.
+    pub fn forward(&mut self) -> bool {
+        let Some(next) = self.at(self.current).children.first().copied() else { return false };
+        let mv = self.at(next).mv.expect("a child has a move");
+        self.board = self.board.make_move(mv).expect("every move in the tree was legal when it was added");
+        self.current = next;
+        true
+    }
+
+    /// Steps back to the parent; false at the root.
+    pub fn back(&mut self) -> bool {
+        match self.at(self.current).parent {
+            Some(parent) => {
+                self.set_cursor(parent);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn go_to(&mut self, id: NodeId) -> Result<()> {
+        self.board = self.board_at(id)?;
+        self.current = id;
+        Ok(())
+    }
+
+    pub fn go_to_start(&mut self) {
+        self.board = self.start.clone();
+        self.current = ROOT;
+    }
+
+    /// Follows the mainline from the cursor to the end of its line.
+    pub fn go_to_end(&mut self) {
+        let mut end = self.current;
+        while let Some(next) = self.at(end).children.first() {
+            end = *next;
+        }
+        if end != self.current {
+            self.set_cursor(end);
+        }
+    }
+
+    /// The nodes from the root down to `id`, root excluded.
+    pub fn path_to(&self, id: NodeId) -> Result<Vec<NodeId>> {
+        let mut path = Vec::new();
+        let mut node = id;
+        while let Some(parent) = self.node(node)?.parent {
+            path.push(node);
+            node = parent;
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    /// The mainline from the root, root excluded.
+    pub fn mainline(&self) -> Vec<NodeId> {
+        let mut line = Vec::new();
+        let mut node = ROOT;
+        while let Some(next) = self.at(node).children.first() {
+            line.push(*next);
+            node = *next;
+        }
+        line
+    }
+
+    /// Makes `id` the first choice at every branch between it and the root, so it
+    /// ends up on the mainline.
+    pub fn promote_to_mainline(&mut self, id: NodeId) -> Result<()> {
+        for node in self.path_to(id)? {
+            let parent = self.at(node).parent.expect("path excludes the root");
+            let siblings = &mut self.at_mut(parent).children;
+            let position = siblings.iter().position(|child| *child == node).expect("child of its parent");
+            let promoted = siblings.remove(position);
+            siblings.insert(0, promoted);
+        }
+        Ok(())
+    }
+
+    /// Removes `id` and everything after it. The cursor moves to the parent if it
+    /// was inside the deleted subtree. The root cannot be deleted.
+    pub fn delete_subtree(&mut self, id: NodeId) -> Result<()> {
+        let parent = self.node(id)?.parent.ok_or(Error::CannotDeleteRoot)?;
+        if self.path_to(self.current)?.contains(&id) {
+            self.set_cursor(parent);
+        }
+        self.at_mut(parent).children.retain(|child| *child != id);
+        let mut doomed = vec![id];
+        while let Some(node) = doomed.pop() {
+            if let Some(removed) = self.nodes[node].take() {
+                doomed.extend(removed.children);
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the tree from a PGN game, variations included.
+    pub fn from_pgn(game: &PgnGame) -> Result<GameTree> {
+        let mut tree = GameTree::new(game.start_board()?);
+        tree.add_pgn_line(ROOT, &game.moves)?;
+        tree.go_to_start();
+        Ok(tree)
+    }
+
+    fn add_pgn_line(&mut self, from: NodeId, line: &[PgnMove]) -> Result<()> {
+        let mut parent = from;
+        for pgn_move in line {
+            if self.current != parent {
+                self.go_to(parent)?;
+            }
+            let id = self.add_san(&pgn_move.san).map_err(|err| Error::InvalidPgn {
+                line: pgn_move.line,
+                reason: format!("'{}': {err}", pgn_move.san),
+            })?;
+            let node = self.at_mut(id);
+            node.comment_before = pgn_move.comment_before.clone();
+            node.comments = pgn_move.comments.clone();
+            node.nags = pgn_move.nags.clone();
+            node.eval = pgn_move.eval;
+            node.clock = pgn_move.clock;
+            for variation in &pgn_move.variations {
+                self.add_pgn_line(parent, variation)?;
+            }
+            parent = id;
+        }
+        Ok(())
+    }
+
+    /// Writes the tree back out as a PGN game with the given tags and result.
+    pub fn to_pgn(&self, tags: Vec<(String, String)>, result: GameResult) -> PgnGame {
+        let mut tags = tags;
+        let start_fen = self.start.to_fen();
+        if start_fen != crate::fen::STANDARD_FEN && !tags.iter().any(|(name, _)| name == "FEN") {
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push(("FEN".to_string(), start_fen));
+        }
+        PgnGame { tags, moves: self.pgn_line(ROOT), result }
+    }
+
+    fn pgn_line(&self, from: NodeId) -> Vec<PgnMove> {
+        let mut line = Vec::new();
+        let mut node = from;
+        while let Some((main, variations)) = self.at(node).children.split_first() {
+            let child = self.at(*main);
+            line.push(PgnMove {
+                san: child.san.clone(),
+                nags: child.nags.clone(),
+                comment_before: child.comment_before.clone(),
+                comments: child.comments.clone(),
+                eval: child.eval,
+                clock: child.clock,
+                variations: variations.iter().map(|v| self.branch(*v)).collect(),
+                ..PgnMove::default()
+            });
+            node = *main;
+        }
+        line
+    }
+
+    /// A variation starting with `id` and following its own mainline.
+    fn branch(&self, id: NodeId) -> Vec<PgnMove> {
+        let node = self.at(id);
+        let mut line = vec![PgnMove {
+            san: node.san.clone(),
+            nags: node.nags.clone(),
+            comment_before: node.comment_before.clone(),
+            comments: node.comments.clone(),
+            eval: node.eval,
+            clock: node.clock,
+            ..PgnMove::default()
+        }];
+        line.extend(self.pgn_line(id));
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::STANDARD_FEN;
+    use crate::pgn::parse_pgn;
+
+    fn tree_with_sicilian_and_french() -> GameTree {
+        let mut tree = GameTree::new(Board::from_fen(STANDARD_FEN).unwrap());
+        tree.add_san("e4").unwrap();
+        let sicilian = tree.add_san("c5").unwrap();
+        tree.add_san("Nf3").unwrap();
+        tree.go_to(sicilian).unwrap();
+        tree.back();
+        tree.add_san("e6").unwrap();
+        tree.add_san("d4").unwrap();
+        tree
+    }
+
+    #[test]
+    fn test_navigation_and_variations() {
+        let mut tree = tree_with_sicilian_and_french();
+        let sans = |tree: &GameTree, ids: Vec<NodeId>| -> Vec<String> {
+            ids.into_iter().map(|id| tree.node(id).unwrap().san.clone()).collect()
+        };
+        assert_eq!(sans(&tree, tree.mainline()), vec!["e4", "c5", "Nf3"]);
+        assert_eq!(tree.board().to_fen(), "rnbqkbnr/pppp1ppp/4p3/8/3PP3/8/PPP2PPP/RNBQKBNR b KQkq - 0 2");
+
+        tree.go_to_start();
+        assert!(!tree.back());
+        assert!(tree.forward());
+        let e4 = tree.current();
+        assert_eq!(tree.node(e4).unwrap().children().len(), 2);
+        // replaying a known move reuses its node
+        let again = tree.add_san("c5").unwrap();
+        assert_eq!(tree.node(again).unwrap().parent(), Some(e4));
+        assert_eq!(tree.node(e4).unwrap().children().len(), 2);
+        tree.go_to_end();
+        assert_eq!(tree.node(tree.current()).unwrap().san, "Nf3");
+
+        let french = tree.node(e4).unwrap().children()[1];
+        tree.promote_to_mainline(tree.node(french).unwrap().children()[0]).unwrap();
+        assert_eq!(sans(&tree, tree.mainline()), vec!["e4", "e6", "d4"]);
+        let d4 = tree.mainline()[2];
+        assert_eq!(tree.node(d4).unwrap().key, tree.board_at(d4).unwrap().position_key());
+    }
+
+    #[test]
+    fn test_replayed_positions_match_the_played_ones() {
+        let mut tree = tree_with_sicilian_and_french();
+        let played = tree.board().clone();
+        let d4 = tree.current();
+        tree.go_to_start();
+        assert_eq!(tree.board().to_fen(), STANDARD_FEN);
+        tree.go_to(d4).unwrap();
+        let differences = played.diff(tree.board());
+        assert!(differences.is_empty(), "{differences:?}");
+
+        // back to e4, then forward along the mainline into the Sicilian
+        tree.back();
+        tree.back();
+        assert!(tree.forward());
+        assert_eq!(tree.node(tree.current()).unwrap().san, "c5");
+        assert!(tree.board_at(tree.current()).unwrap().diff(tree.board()).is_empty());
+    }
+
+    #[test]
+    fn test_delete_subtree_moves_the_cursor_out() {
+        let mut tree = tree_with_sicilian_and_french();
+        let e4 = tree.mainline()[0];
+        let french = tree.node(e4).unwrap().children()[1];
+        let d4 = tree.current();
+        tree.delete_subtree(french).unwrap();
+        assert_eq!(tree.current(), e4);
+        assert_eq!(tree.node(e4).unwrap().children().len(), 1);
+        assert_eq!(tree.node(d4).unwrap_err(), Error::UnknownNode(d4));
+        assert_eq!(tree.delete_subtree(tree.root()), Err(Error::CannotDeleteRoot));
+    }
+
+    #[test]
+    fn test_pgn_round_trip_through_the_tree() {
+        let text = "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n\
+                    [Black \"?\"]\n[Result \"*\"]\n\n1. e4 $1 {best by test} 1... c5 (1... e5 2. Nf3 (2. f4)) 2. Nf3 *\n";
+        let game = &parse_pgn(text).unwrap()[0];
+        let tree = GameTree::from_pgn(game).unwrap();
+        assert_eq!(tree.node(tree.mainline()[0]).unwrap().nags, vec![1]);
+        let back_out = tree.to_pgn(game.tags.clone(), game.result);
+        assert_eq!(back_out.to_pgn(), text);
+
+        // a comment before a move stays in front of it, in the mainline and in a variation
+        let text = text.replace("1. e4 $1", "{Go} 1. e4 $1").replace("(1... e5", "({Or} 1... e5");
+        let game = &parse_pgn(&text).unwrap()[0];
+        let tree = GameTree::from_pgn(game).unwrap();
+        assert_eq!(tree.node(tree.mainline()[0]).unwrap().comments, vec!["best by test".to_string()]);
+        assert_eq!(tree.to_pgn(game.tags.clone(), game.result).to_pgn(), text);
+    }
+}
@@ -0,0 +1,128 @@
+// src/golden.rs
+// Golden snapshots of the exchanger dump. Each case is a position from the
+// library and a move sequence; its snapshot is the `canonical_string` of the start and of the board
+// after every move. The recorded text lives in testdata/golden and any change to
+// it fails the test. Run the tests with UPDATE_GOLDEN=1 to re-record after an
+// intended change, then review the file diff.
+use crate::board::Board;
+use crate::error::Result;
+use std::fmt::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy)]
+pub struct GoldenCase {
+    pub name: &'static str,
+    /// The name of the starting position in `positions::POSITIONS`.
+    pub position: &'static str,
+    /// SAN moves played from the position, each through the full rebuild.
+    pub moves: &'static [&'static str],
+}
+
+pub const GOLDEN_CASES: &[GoldenCase] = &[
+    GoldenCase {
+        name: "standard opening",
+        position: "standard",
+        moves: &["e4", "e5", "Nf3", "Nc6", "Bb5"],
+    },
+    GoldenCase {
+        name: "double discovered check",
+        position: "double_discovered_check",
+        moves: &["Bf1+", "b5", "axb6"],
+    },
+    GoldenCase {
+        name: "pinned knight",
+        position: "pinned_knight",
+        moves: &["Kd1", "Rxe2"],
+    },
+    GoldenCase {
+        name: "castling and promotion",
+        position: "castle_and_promote",
+        moves: &["exd6", "O-O", "bxa8=Q", "Rxa8", "O-O-O"],
+    },
+];
+
+impl GoldenCase {
+    pub fn start(&self) -> Result<Board> {
+        Board::named(self.position)
+    }
+
+    pub fn snapshot(&self) -> Result<String> {
+        let mut board = self.start()?;
+        let mut out = String::new();
+        writeln!(out, "== {} ==", self.name).unwrap();
+        out.push_str(&board.canonical_string());
+        for san in self.moves {
+            board = board.play_san(san)?;
+            writeln!(out, "-- {san} --").unwrap();
+            out.push_str(&board.canonical_string());
+        }
+        Ok(out)
+    }
+}
+
+pub fn snapshot_all(cases: &[GoldenCase]) -> Result<String> {
+    let snapshots = cases.iter().map(GoldenCase::snapshot).collect::<Result<Vec<_>>>()?;
+    Ok(snapshots.join("\n"))
+}
+
+/// Compares `actual` with the golden file, naming the first differing line. With
+/// UPDATE_GOLDEN set the file is rewritten instead.
+pub fn check_golden(path: &Path, actual: &str) -> std::result::Result<(), String> {
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        return std::fs::write(path, actual).map_err(|err| format!("cannot write {}: {err}", path.display()));
+    }
+    let expected = std::fs::read_to_string(path)
+        .map_err(|err| format!("cannot read {} ({err}); run with UPDATE_GOLDEN=1 to record it", path.display()))?;
+    if expected == actual {
+        return Ok(());
+    }
+    let mut context = "";
+    for (number, pair) in expected.lines().zip(actual.lines()).enumerate() {
+        if pair.0.starts_with("==") || pair.0.starts_with("--") {
+            context = pair.0;
+        }
+        if pair.0 != pair.1 {
+            return Err(format!(
+                "{} line {} (under '{context}'):\n  expected: {}\n  actual:   {}",
+                path.display(),
+                number + 1,
+                pair.0,
+                pair.1
+            ));
+        }
+    }
+    Err(format!(
+        "{}: expected {} lines, got {}",
+        path.display(),
+        expected.lines().count(),
+        actual.lines().count()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pieces::Side;
+    use std::path::PathBuf;
+
+    fn golden_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/golden").join(name)
+    }
+
+    #[test]
+    fn test_exchanger_dumps_match_golden_file() {
+        let actual = snapshot_all(GOLDEN_CASES).unwrap();
+        if let Err(report) = check_golden(&golden_path("exchangers.txt"), &actual) {
+            panic!("{report}");
+        }
+    }
+
+    #[test]
+    fn test_canonical_string_ignores_hashing() {
+        // the same position built in a different order hashes its pieces differently
+        let forward = Board::from_pid_list(vec!["e1K", "e8k", "e2N", "e7r"], Side::White).unwrap();
+        let backward = Board::from_pid_list(vec!["e7r", "e2N", "e8k", "e1K"], Side::White).unwrap();
+        assert_eq!(forward.canonical_string(), backward.canonical_string());
+        assert!(forward.canonical_string().starts_with("fen: 4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1\nchecks: none\ne1K |"));
+    }
+}
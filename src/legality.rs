@@ -0,0 +1,246 @@
+// src/legality.rs
+// Checks that a set-up position could arise in a game. This is about the chess
+// position, not the board's internal bookkeeping - see `Board::validate` for that.
+use crate::attacks::is_attacked;
+use crate::board::{Board, Rank, Square};
+use crate::pieces::Side;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// Each side needs exactly one king.
+    KingCount { side: Side, count: usize },
+    /// The setup list named the same square more than once.
+    DuplicateSquare(Square),
+    /// Pawns can never stand on the first or eighth rank.
+    PawnOnBackRank(Square),
+    TooManyPieces { side: Side, count: usize },
+    TooManyPawns { side: Side, count: usize },
+    /// More queens, rooks, bishops or knights than the missing pawns could have promoted to.
+    ImpossiblePromotions { side: Side, promoted: usize, missing_pawns: usize },
+    /// The side that just moved has left its own king attacked.
+    OpponentInCheck(Side),
+    EnPassant { square: Square, reason: String },
+    Castling { right: char, reason: String },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::KingCount { side, count } => write!(f, "{side:?} has {count} kings, expected 1"),
+            Violation::DuplicateSquare(square) => write!(f, "more than one piece placed on {square}"),
+            Violation::PawnOnBackRank(square) => write!(f, "pawn on back rank square {square}"),
+            Violation::TooManyPieces { side, count } => write!(f, "{side:?} has {count} pieces, at most 16 allowed"),
+            Violation::TooManyPawns { side, count } => write!(f, "{side:?} has {count} pawns, at most 8 allowed"),
+            Violation::ImpossiblePromotions { side, promoted, missing_pawns } => write!(
+                f,
+                "{side:?} needs {promoted} promotions but is only missing {missing_pawns} pawns"
+            ),
+            Violation::OpponentInCheck(side) => write!(f, "{side:?} is in check but it is not their move"),
+            Violation::EnPassant { square, reason } => write!(f, "en passant square {square}: {reason}"),
+            Violation::Castling { right, reason } => write!(f, "castling right '{right}': {reason}"),
+        }
+    }
+}
+
+/// Every reason the board's position is illegal, in a stable order.
+pub fn check_position(board: &Board) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for side in [Side::White, Side::Black] {
+        check_material(board, side, &mut violations);
+    }
+
+    let mut back_rank_pawns: Vec<Square> = board
+        .iter_pieces()
+        .filter(|(square, piece)| {
+            piece.get_piece_type_as_char().eq_ignore_ascii_case(&'P')
+                && matches!(square.rank(), Rank::R1 | Rank::R8)
+        })
+        .map(|(square, _)| *square)
+        .collect();
+    back_rank_pawns.sort_by_key(|square| square.index());
+    violations.extend(back_rank_pawns.into_iter().map(Violation::PawnOnBackRank));
+
+    let waiting = board.turn().opposite();
+    if let Some(king) = king_square(board, waiting)
+        && is_attacked(board, king, board.turn())
+    {
+        violations.push(Violation::OpponentInCheck(waiting));
+    }
+
+    if let Some(square) = board.en_passant_square() {
+        check_en_passant(board, square, &mut violations);
+    }
+    check_castling(board, &mut violations);
+    violations
+}
+
+fn count(board: &Board, pchar: char) -> usize {
+    board.iter_pieces().filter(|(_, piece)| piece.get_piece_type_as_char() == pchar).count()
+}
+
+fn side_char(side: Side, pchar: char) -> char {
+    match side {
+        Side::White => pchar.to_ascii_uppercase(),
+        Side::Black => pchar.to_ascii_lowercase(),
+    }
+}
+
+fn check_material(board: &Board, side: Side, violations: &mut Vec<Violation>) {
+    let kings = count(board, side_char(side, 'K'));
+    if kings != 1 {
+        violations.push(Violation::KingCount { side, count: kings });
+    }
+    let pieces = board.iter_pieces().filter(|(_, piece)| piece.get_side() == side).count();
+    if pieces > 16 {
+        violations.push(Violation::TooManyPieces { side, count: pieces });
+    }
+    let pawns = count(board, side_char(side, 'P'));
+    if pawns > 8 {
+        violations.push(Violation::TooManyPawns { side, count: pawns });
+    }
+
+    // anything beyond the starting set must have come from a pawn
+    let promoted: usize = [('Q', 1), ('R', 2), ('B', 2), ('N', 2)]
+        .iter()
+        .map(|(pchar, initial)| count(board, side_char(side, *pchar)).saturating_sub(*initial))
+        .sum();
+    let missing_pawns = 8usize.saturating_sub(pawns);
+    if promoted > missing_pawns {
+        violations.push(Violation::ImpossiblePromotions { side, promoted, missing_pawns });
+    }
+}
+
+fn king_square(board: &Board, side: Side) -> Option<Square> {
+    let king = side_char(side, 'K');
+    let mut kings = board.iter_pieces().filter(|(_, piece)| piece.get_piece_type_as_char() == king);
+    match (kings.next(), kings.next()) {
+        (Some((square, _)), None) => Some(*square),
+        _ => None,
+    }
+}
+
+fn check_en_passant(board: &Board, square: Square, violations: &mut Vec<Violation>) {
+    let mut fail = |reason: &str| violations.push(Violation::EnPassant { square, reason: reason.to_string() });
+
+    // the side to move captures, so the pawn that just double-stepped is the opponent's
+    let (expected_rank, pawn, origin_rank) = match board.turn() {
+        Side::White => (Rank::R6, 'p', Rank::R7),
+        Side::Black => (Rank::R3, 'P', Rank::R2),
+    };
+    if square.rank() != expected_rank {
+        fail(&format!("must be on rank {} with {:?} to move", expected_rank.to_char(), board.turn()));
+        return;
+    }
+    if board.get_piece_on(square).is_some() {
+        fail("square is occupied");
+    }
+    let origin = Square::from_file_rank(square.file(), origin_rank);
+    if board.get_piece_on(origin).is_some() {
+        fail(&format!("{origin} is occupied, so no pawn can have just left it"));
+    }
+    let victim = square.en_passant_victim();
+    if board.get_piece_on(victim).map(|piece| piece.get_piece_type_as_char()) != Some(pawn) {
+        fail(&format!("no pawn on {victim} to have made the double step"));
+    }
+}
+
+fn check_castling(board: &Board, violations: &mut Vec<Violation>) {
+    let rights = board.castling_rights();
+    let required = [
+        (rights.white_king_side, 'K', Square::e1, 'K', Square::h1, 'R'),
+        (rights.white_queen_side, 'Q', Square::e1, 'K', Square::a1, 'R'),
+        (rights.black_king_side, 'k', Square::e8, 'k', Square::h8, 'r'),
+        (rights.black_queen_side, 'q', Square::e8, 'k', Square::a8, 'r'),
+    ];
+    for (held, right, king_sq, king, rook_sq, rook) in required {
+        if !held {
+            continue;
+        }
+        let on = |square: Square| board.get_piece_on(square).map(|piece| piece.get_piece_type_as_char());
+        if on(king_sq) != Some(king) {
+            violations.push(Violation::Castling { right, reason: format!("king is not on {king_sq}") });
+        } else if on(rook_sq) != Some(rook) {
+            violations.push(Violation::Castling { right, reason: format!("rook is not on {rook_sq}") });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::CastlingRights;
+    use crate::error::Error;
+
+    fn board_from(pids: Vec<&str>) -> Board {
+        let mut board = Board::new();
+        board.init_from_pid_list(pids).unwrap();
+        board
+    }
+
+    #[test]
+    fn test_legal_positions_pass() {
+        let mut board = Board::new();
+        board.init_standard().unwrap();
+        board.set_castling_rights(CastlingRights::ALL);
+        assert_eq!(check_position(&board), vec![]);
+
+        let board = Board::from_pid_list(vec!["e1K", "e8k", "e4P", "d4p"], Side::Black);
+        assert!(board.is_ok());
+    }
+
+    #[test]
+    fn test_every_violation_is_reported() {
+        let mut board = board_from(vec!["e1K", "e2K", "a1P", "h8p", "b8q", "c8q", "d8q", "a7p", "b7p", "c7p", "d7p", "e7p", "f7p"]);
+        // black's king is missing entirely; white has two
+        let violations = check_position(&board);
+        assert_eq!(
+            violations,
+            vec![
+                Violation::KingCount { side: Side::White, count: 2 },
+                Violation::KingCount { side: Side::Black, count: 0 },
+                Violation::ImpossiblePromotions { side: Side::Black, promoted: 2, missing_pawns: 1 },
+                Violation::PawnOnBackRank(Square::a1),
+                Violation::PawnOnBackRank(Square::h8),
+            ]
+        );
+
+        board.set_castling_rights(CastlingRights { white_king_side: true, ..CastlingRights::NONE });
+        assert_eq!(
+            check_position(&board).last(),
+            Some(&Violation::Castling { right: 'K', reason: "rook is not on h1".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_opponent_in_check_and_en_passant() {
+        // white to move, yet black's king sits on the h1 bishop's diagonal
+        let board = board_from(vec!["e1K", "a8k", "h1B"]);
+        assert_eq!(check_position(&board), vec![Violation::OpponentInCheck(Side::Black)]);
+
+        let mut board = board_from(vec!["e1K", "e8k", "d5P", "e5p"]);
+        board.set_en_passant_square(Some(Square::e6));
+        assert_eq!(check_position(&board), vec![]);
+
+        board.set_en_passant_square(Some(Square::d6));
+        assert_eq!(
+            check_position(&board),
+            vec![Violation::EnPassant { square: Square::d6, reason: "no pawn on d5 to have made the double step".to_string() }]
+        );
+
+        board.set_en_passant_square(Some(Square::e3));
+        assert!(matches!(check_position(&board)[..], [Violation::EnPassant { square: Square::e3, .. }]));
+
+        let err = Board::from_pid_list(vec!["e1K", "e8k", "e8P"], Side::White).unwrap_err();
+        assert_eq!(
+            err,
+            Error::IllegalPosition(vec![
+                Violation::DuplicateSquare(Square::e8),
+                Violation::KingCount { side: Side::Black, count: 0 },
+                Violation::PawnOnBackRank(Square::e8),
+            ])
+        );
+        let err = Board::from_pid_list(vec!["e1K", "e8k", "a8P"], Side::White).unwrap_err();
+        assert_eq!(err, Error::IllegalPosition(vec![Violation::PawnOnBackRank(Square::a8)]));
+    }
+}
@@ -1,12 +1,38 @@
+pub mod attack_map;
+pub mod attacks;
 pub mod board;
+pub mod board_builder;
+pub mod board_diff;
 // pub mod x_map;
 pub mod compass_groups;
+pub mod diagram;
+pub mod dot;
+pub mod epd;
+pub mod error;
+pub mod eval;
+pub mod fen;
+pub mod game;
+pub mod game_tree;
+pub mod golden;
+pub mod legality;
+pub mod logging;
+pub mod magic_bitboards;
+pub mod moves;
 pub mod occupied_squares;
+pub mod pgn;
 pub mod pid;
 pub mod pieces;
+pub mod positions;
+pub mod processing_stats;
+pub mod repl;
+pub mod scenario;
+pub mod search;
+pub mod svg;
+pub mod zobrist;
 
 
 pub use crate::board::Board as ChessBoard;
+pub use crate::error::Error;
 
 pub use compass_groups::*;
 pub use pieces::*;
@@ -0,0 +1,39 @@
+// Minimal stderr backend for the `log` facade. Diagnostics never go to stdout, so
+// they cannot corrupt protocol output; the level is chosen at start-up.
+use log::{LevelFilter, Log, Metadata, Record};
+use std::str::FromStr;
+
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Installs the stderr logger (once) and sets the maximum level. Calling it again
+/// only changes the level.
+pub fn init(level: LevelFilter) {
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+}
+
+/// Reads the level from the `NEETROC_LOG` environment variable (e.g. "debug"),
+/// falling back to `default` when it is unset or unparseable.
+pub fn init_from_env(default: LevelFilter) {
+    let level = std::env::var("NEETROC_LOG")
+        .ok()
+        .and_then(|value| LevelFilter::from_str(&value).ok())
+        .unwrap_or(default);
+    init(level);
+}
@@ -0,0 +1,296 @@
+// src/magic_bitboards.rs
+// Fancy-magic slider attack lookup. The attack tables are generated once, on first
+// use, from the ray tables in occupied_squares. The magic multipliers below were
+// found by `find_magic`; a square whose stored magic fails verification falls back
+// to searching again, so the constants can never silently corrupt the tables.
+use crate::board::Square;
+use crate::compass_groups::Direction;
+use crate::occupied_squares::{NEIGHBOURS, RAYS};
+use std::sync::LazyLock as Lazy;
+
+const ROOK_DIRECTIONS: [Direction; 4] = [Direction::N, Direction::E, Direction::S, Direction::W];
+const BISHOP_DIRECTIONS: [Direction; 4] = [Direction::NE, Direction::SE, Direction::SW, Direction::NW];
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupied: u64) -> usize {
+        self.offset + (((occupied & self.mask).wrapping_mul(self.magic)) >> self.shift) as usize
+    }
+}
+
+struct SliderTable {
+    magics: [Magic; 64],
+    attacks: Vec<u64>,
+}
+
+impl SliderTable {
+    fn attacks(&self, square: Square, occupied: u64) -> u64 {
+        self.attacks[self.magics[square.index()].index(occupied)]
+    }
+}
+
+const ROOK_MAGICS: [u64; 64] = [
+    0x1080_0040_0880_1020, 0x0840_0920_02C0_3000, 0x1900_2000_1040_0900, 0x0880_1000_0800_0480,
+    0x4200_1004_2008_0200, 0x8100_0201_0008_0400, 0x0200_0401_1088_6200, 0x0200_0080_4022_0411,
+    0x0404_8000_8440_0220, 0x0000_4010_0040_2000, 0x0086_0010_8122_0440, 0x0408_8008_0010_0280,
+    0x000A_0012_0104_0820, 0x8848_8002_0084_0080, 0x4001_0001_0004_0200, 0x0442_0001_0210_5084,
+    0x9080_0100_2080_4100, 0x0040_4040_0020_1009, 0x0000_8080_1000_2009, 0x2200_0900_21D0_0100,
+    0x0008_0080_0804_0080, 0x0004_0040_0201_0040, 0x0011_0400_0801_5042, 0x0000_0A00_0176_8104,
+    0x0000_8000_8020_4009, 0x2010_0041_4000_2001, 0x9800_2002_8010_0080, 0x1000_1000_8008_0080,
+    0x0442_000A_0004_9020, 0x2100_0400_8002_0080, 0x0800_1204_0090_0148, 0x0010_040A_0012_8541,
+    0x2800_8040_0080_0030, 0x1010_0020_0040_0041, 0x4000_2000_1100_4100, 0x0610_0084_1080_0800,
+    0x0400_8024_0280_0800, 0xC100_0200_8080_0400, 0x0002_0008_0200_0401, 0x0182_0858_8200_0401,
+    0x0220_2040_0080_8000, 0x2860_1000_4002_4022, 0x0001_0020_0411_0040, 0x9910_1042_000A_0020,
+    0x0004_0800_0400_8080, 0x0010_0400_0200_8080, 0x2012_0048_8102_0004, 0x8300_8424_4482_0011,
+    0x0088_4038_8201_0200, 0x0820_4000_8021_0100, 0x0110_9100_40A0_0300, 0x0801_1002_8008_0480,
+    0x0242_0090_0820_0600, 0x1002_0004_8950_0200, 0x0040_8002_0001_0080, 0x0091_8000_4100_0080,
+    0x0000_2093_0048_8001, 0x04C1_0024_1482_4001, 0x0200_2000_0B00_1041, 0x7000_1000_0420_0901,
+    0x8002_0020_0410_0802, 0x3001_0002_084C_0007, 0x0888_2218_0081_3004, 0x4000_0028_4084_0112,
+];
+
+const BISHOP_MAGICS: [u64; 64] = [
+    0x1010_2002_004A_1420, 0x8020_0404_0058_4008, 0x1051_0800_8112_01C8, 0x5204_0420_8000_0088,
+    0x2204_1068_8000_0002, 0x1401_0420_0400_0000, 0x0400_8804_1004_2004, 0x0028_2082_00A0_2020,
+    0x1500_2419_9001_0E00, 0x8001_2001_8202_0A40, 0x4000_4101_030B_0000, 0x8002_0410_4200_0100,
+    0x4010_0110_4102_0038, 0x0000_0104_2104_4000, 0x1500_2108_0802_0A00, 0x8000_0884_0088_0520,
+    0x0405_0040_1004_0100, 0x1005_8232_1004_0108, 0x2708_0081_0204_0011, 0x4048_2004_0400_9100,
+    0x0018_1041_0140_0024, 0x0003_0006_0119_0101, 0x8004_8031_0849_1000, 0x8014_2412_0082_0800,
+    0x0006_E080_100C_3040, 0x0501_044A_1104_1800, 0x9020_3000_0800_4045, 0x0894_0800_0022_0040,
+    0x1001_0100_8310_4000, 0x5004_0300_4090_0080, 0x0004_0042_2C01_2400, 0x0002_1286_9840_4812,
+    0x1010_1084_0490_0440, 0x0928_0211_8208_4100, 0x2006_0804_0902_0024, 0x1010_2020_2018_0080,
+    0xA010_0082_0020_2200, 0x2098_0151_0001_9004, 0x0002_0414_4081_0811, 0x802A_0202_0000_B098,
+    0x0009_0150_9000_4060, 0x4000_8210_8208_1001, 0x0100_2100_4042_0800, 0x0800_0040_1048_8A00,
+    0x2000_0811_0400_4040, 0x4C8E_0290_1500_0082, 0x0420_3403_2222_4842, 0x1298_2600_4340_0210,
+    0x0000_8228_0240_0008, 0x0000_8A01_0160_0000, 0x3040_0034_1208_0021, 0x3040_2902_2088_4800,
+    0x4A15_0040_1041_004A, 0x8010_2002_8202_0781, 0x0020_2031_4220_9091, 0x0070_3006_0090_2110,
+    0x0040_8088_00B6_2048, 0x0000_8104_00C4_4420, 0x0008_0400_440C_0441, 0x8340_0800_2084_0411,
+    0x0000_0001_0420_8200, 0x0000_8008_10D0_0080, 0x0400_5304_1108_0200, 0x4040_7024_0093_2244,
+];
+
+static ROOK_TABLE: Lazy<SliderTable> = Lazy::new(|| build_table(&ROOK_DIRECTIONS, &ROOK_MAGICS));
+static BISHOP_TABLE: Lazy<SliderTable> = Lazy::new(|| build_table(&BISHOP_DIRECTIONS, &BISHOP_MAGICS));
+
+pub fn rook_attacks(square: Square, occupied: u64) -> u64 {
+    ROOK_TABLE.attacks(square, occupied)
+}
+
+pub fn bishop_attacks(square: Square, occupied: u64) -> u64 {
+    BISHOP_TABLE.attacks(square, occupied)
+}
+
+pub fn queen_attacks(square: Square, occupied: u64) -> u64 {
+    rook_attacks(square, occupied) | bishop_attacks(square, occupied)
+}
+
+/// The squares attacked along a single direction, up to and including the first blocker.
+/// Half winds only ever cover the one knight-step square.
+pub fn attacks_along(square: Square, direction: Direction, occupied: u64) -> u64 {
+    let ray = RAYS[square.index()][direction as usize];
+    if direction.is_half_wind() {
+        ray
+    } else if ROOK_DIRECTIONS.contains(&direction) {
+        rook_attacks(square, occupied) & ray
+    } else {
+        bishop_attacks(square, occupied) & ray
+    }
+}
+
+/// The first occupied square met along a direction.
+pub fn first_blocker(square: Square, direction: Direction, occupied: u64) -> Option<Square> {
+    let blocker = attacks_along(square, direction, occupied) & occupied;
+    if blocker == 0 {
+        None
+    } else {
+        Square::from_index(blocker.trailing_zeros() as usize)
+    }
+}
+
+/// The occupied square sitting directly behind the first blocker: the x-ray continuation.
+pub fn xray_blocker(square: Square, direction: Direction, occupied: u64) -> Option<Square> {
+    if direction.is_half_wind() {
+        return None;
+    }
+    let first = first_blocker(square, direction, occupied)?;
+    first_blocker(square, direction, occupied & !(1u64 << first.index()))
+}
+
+// Attacks found by walking the rays square by square; used to fill the tables.
+fn walked_attacks(square: Square, directions: &[Direction], occupied: u64) -> u64 {
+    let mut attacks = 0u64;
+    for direction in directions {
+        let mut current = NEIGHBOURS[square.index()][*direction as usize];
+        while let Some(sq) = current {
+            let bit = 1u64 << sq.index();
+            attacks |= bit;
+            if occupied & bit != 0 {
+                break;
+            }
+            current = NEIGHBOURS[sq.index()][*direction as usize];
+        }
+    }
+    attacks
+}
+
+// Relevant occupancy: every ray square except the last one, whose occupancy never
+// changes the attack set.
+fn relevant_mask(square: Square, directions: &[Direction]) -> u64 {
+    let mut mask = 0u64;
+    for direction in directions {
+        let mut current = NEIGHBOURS[square.index()][*direction as usize];
+        while let Some(sq) = current {
+            current = NEIGHBOURS[sq.index()][*direction as usize];
+            if current.is_some() {
+                mask |= 1u64 << sq.index();
+            }
+        }
+    }
+    mask
+}
+
+// xorshift64*: small, deterministic and good enough to find magics quickly.
+struct MagicRng(u64);
+
+impl MagicRng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn sparse(&mut self) -> u64 {
+        self.next() & self.next() & self.next()
+    }
+}
+
+// Fills one square's slots with `magic`, failing on a destructive collision.
+fn try_magic(
+    magic: &Magic,
+    occupancies: &[u64],
+    references: &[u64],
+    attacks: &mut [u64],
+    used: &mut [u32],
+    attempt: u32,
+) -> bool {
+    for (occupancy, reference) in occupancies.iter().zip(references) {
+        let slot = magic.index(*occupancy);
+        if used[slot - magic.offset] != attempt {
+            used[slot - magic.offset] = attempt;
+            attacks[slot] = *reference;
+        } else if attacks[slot] != *reference {
+            return false;
+        }
+    }
+    true
+}
+
+fn find_magic(
+    magic: &mut Magic,
+    occupancies: &[u64],
+    references: &[u64],
+    attacks: &mut [u64],
+    used: &mut [u32],
+) {
+    let mut rng = MagicRng(0x9E37_79B9_7F4A_7C15 ^ magic.mask);
+    let mut attempt = 1u32;
+    loop {
+        attempt += 1;
+        magic.magic = rng.sparse();
+        if (magic.mask.wrapping_mul(magic.magic) >> 56).count_ones() < 6 {
+            continue;
+        }
+        if try_magic(magic, occupancies, references, attacks, used, attempt) {
+            return;
+        }
+    }
+}
+
+fn build_table(directions: &[Direction], stored: &[u64; 64]) -> SliderTable {
+    let mut magics = [Magic::default(); 64];
+    let mut attacks: Vec<u64> = Vec::new();
+
+    for (idx, magic) in magics.iter_mut().enumerate() {
+        let square = Square::from_index(idx).unwrap();
+        let mask = relevant_mask(square, directions);
+        let bits = mask.count_ones();
+
+        // Enumerate every subset of the mask (Carry-Rippler) with its reference attacks.
+        let mut occupancies: Vec<u64> = Vec::with_capacity(1 << bits);
+        let mut references: Vec<u64> = Vec::with_capacity(1 << bits);
+        let mut subset = 0u64;
+        loop {
+            occupancies.push(subset);
+            references.push(walked_attacks(square, directions, subset));
+            subset = subset.wrapping_sub(mask) & mask;
+            if subset == 0 {
+                break;
+            }
+        }
+
+        let offset = attacks.len();
+        attacks.resize(offset + (1 << bits), 0);
+        let mut used = vec![0u32; 1 << bits];
+        *magic = Magic { mask, magic: stored[idx], shift: 64 - bits, offset };
+
+        if !try_magic(magic, &occupancies, &references, &mut attacks, &mut used, 1) {
+            find_magic(magic, &occupancies, &references, &mut attacks, &mut used);
+        }
+    }
+
+    SliderTable { magics, attacks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::ALL_SQUARES;
+
+    #[test]
+    fn test_magic_attacks_match_walked_rays() {
+        let occupancies = [
+            0u64,
+            0x0000_0010_0800_0000,
+            0xFFFF_0000_0000_FFFF,
+            0x0042_0018_2400_8100,
+            0x8100_0000_0000_0081,
+        ];
+        for square in ALL_SQUARES {
+            for occupied in occupancies {
+                assert_eq!(rook_attacks(square, occupied), walked_attacks(square, &ROOK_DIRECTIONS, occupied));
+                assert_eq!(bishop_attacks(square, occupied), walked_attacks(square, &BISHOP_DIRECTIONS, occupied));
+            }
+        }
+    }
+
+    #[test]
+    fn test_magic_search_recovers_from_bad_constant() {
+        let mut stored = ROOK_MAGICS;
+        stored[Square::d4.index()] = 0;
+        let table = build_table(&ROOK_DIRECTIONS, &stored);
+        for occupied in [0u64, 0x0042_0018_2400_8100] {
+            assert_eq!(table.attacks(Square::d4, occupied), walked_attacks(Square::d4, &ROOK_DIRECTIONS, occupied));
+        }
+    }
+
+    #[test]
+    fn test_first_and_xray_blockers() {
+        let mut occupied = 0u64;
+        for square in [Square::a1, Square::a4, Square::a7, Square::d4] {
+            occupied |= 1u64 << square.index();
+        }
+        assert_eq!(first_blocker(Square::a1, Direction::N, occupied), Some(Square::a4));
+        assert_eq!(xray_blocker(Square::a1, Direction::N, occupied), Some(Square::a7));
+        assert_eq!(first_blocker(Square::a7, Direction::S, occupied), Some(Square::a4));
+        assert_eq!(xray_blocker(Square::a7, Direction::S, occupied), Some(Square::a1));
+        assert_eq!(first_blocker(Square::a4, Direction::SE, occupied), None);
+        assert_eq!(first_blocker(Square::a4, Direction::E, occupied), Some(Square::d4));
+        assert_eq!(xray_blocker(Square::a4, Direction::E, occupied), None);
+    }
+}
@@ -1,16 +1,25 @@
-use neetroc_bot::board::move_framework;
-// use neetroc_bot::board::Board;
-use neetroc_bot::board::Square::*;
+// Interactive shell for exploring positions and the exchanger engine; `help`
+// lists the commands. Reads one command per line until `quit` or end of input.
+use neetroc_bot::logging;
+use neetroc_bot::repl::{Reply, Session, HELP};
+use std::io::{self, BufRead, Write};
+
 fn main() {
-    // let mut board = Board::new();
-    // // board.init_standard();
-    // board.init_double_discovered_check();
-    // board.build_all_xchngrs();
-    // println!("Main board:");
-    // println!("{:?}", board); // NNB too heavy for debug runs
+    logging::init_from_env(log::LevelFilter::Warn);
 
-    move_framework(
-        vec!["e1K", "a6k", "a5P", "b7p", "a1R", "g2B", "g1B", "d8N"],
-        vec![(g2, f1), (b7, b5), (a5, b6)]
-    );
+    let mut session = Session::new();
+    println!("{HELP}");
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush().ok();
+        let Some(Ok(line)) = lines.next() else { break };
+        match session.execute(&line) {
+            Ok(Reply::Text(text)) if text.is_empty() => {}
+            Ok(Reply::Text(text)) => println!("{text}"),
+            Ok(Reply::Quit) => break,
+            Err(err) => println!("error: {err}"),
+        }
+    }
 }
@@ -0,0 +1,465 @@
+// src/moves.rs
+// Legal move generation and the two move notations: SAN as people write it
+// ("Nbd7", "exd6", "e8=Q+", "O-O") and UCI long algebraic ("e2e4", "e7e8q").
+use crate::attacks::{attackers_of_with, piece_attacks, squares_of};
+use crate::board::{Board, File, Rank, Square};
+use crate::compass_groups::Direction;
+use crate::error::{Error, Result};
+use crate::pieces::Side;
+use std::fmt;
+
+const PROMOTIONS: [char; 4] = ['Q', 'R', 'B', 'N'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    /// The piece a pawn becomes on the last rank, always upper case: 'Q', 'R', 'B' or 'N'.
+    pub promotion: Option<char>,
+}
+
+impl Move {
+    pub const fn new(from: Square, to: Square) -> Self {
+        Move { from, to, promotion: None }
+    }
+
+    pub fn with_promotion(from: Square, to: Square, piece: char) -> Self {
+        Move { from, to, promotion: Some(piece.to_ascii_uppercase()) }
+    }
+
+    /// Parses long algebraic as UCI sends it. Only the syntax is checked here;
+    /// `Board::parse_uci` also checks the move is legal.
+    pub fn from_uci(uci: &str) -> Result<Move> {
+        let invalid = |reason: &str| Error::InvalidNotation { notation: uci.to_string(), reason: reason.to_string() };
+        let chars: Vec<char> = uci.trim().chars().collect();
+        if chars.len() != 4 && chars.len() != 5 {
+            return Err(invalid("expected four or five characters"));
+        }
+        let from = Square::from_chars(chars[0], chars[1]).ok_or_else(|| invalid("bad origin square"))?;
+        let to = Square::from_chars(chars[2], chars[3]).ok_or_else(|| invalid("bad destination square"))?;
+        match chars.get(4) {
+            None => Ok(Move::new(from, to)),
+            Some(c) if PROMOTIONS.contains(&c.to_ascii_uppercase()) => Ok(Move::with_promotion(from, to, *c)),
+            Some(_) => Err(invalid("promotion must be one of q, r, b or n")),
+        }
+    }
+
+    pub fn to_uci(&self) -> String {
+        match self.promotion {
+            Some(piece) => format!("{}{}{}", self.from, self.to, piece.to_ascii_lowercase()),
+            None => format!("{}{}", self.from, self.to),
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_uci())
+    }
+}
+
+impl Board {
+    fn piece_char_on(&self, square: Square) -> Option<char> {
+        self.get_piece_on(square).map(|piece| piece.get_piece_type_as_char())
+    }
+
+    fn side_bits(&self, side: Side) -> u64 {
+        self.iter_pieces()
+            .filter(|(_, piece)| piece.get_side() == side)
+            .fold(0u64, |bits, (square, _)| bits | (1u64 << square.index()))
+    }
+
+    /// Whether the side to move has its king attacked.
+    pub fn in_check(&self) -> bool {
+        let side = self.turn();
+        self.king_location(side).is_some_and(|king| {
+            attackers_of_with(self, king, side.opposite(), self.get_occupied_bitboard()) != 0
+        })
+    }
+
+    pub fn is_checkmate(&self) -> bool {
+        self.in_check() && self.legal_moves().is_empty()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        !self.in_check() && self.legal_moves().is_empty()
+    }
+
+    /// Every legal move for the side to move, ordered by origin then destination square.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let side = self.turn();
+        let own = self.side_bits(side);
+        let enemy = self.side_bits(side.opposite());
+        let occupied = self.get_occupied_bitboard();
+
+        let mut squares: Vec<Square> = self
+            .iter_pieces()
+            .filter(|(_, piece)| piece.get_side() == side)
+            .map(|(square, _)| *square)
+            .collect();
+        squares.sort_by_key(|square| square.index());
+
+        let mut moves = Vec::new();
+        for from in squares {
+            let piece = self.get_piece_on(from).expect("square taken from the pieces map");
+            let Ok(data) = piece.get_piece_data() else { continue };
+            let pchar = piece.get_piece_type_as_char().to_ascii_uppercase();
+            let targets = if pchar == 'P' {
+                self.pawn_targets(from, side, enemy, occupied)
+            } else {
+                piece_attacks(data, from, occupied) & !own
+            };
+            let last_rank = if side == Side::White { Rank::R8 } else { Rank::R1 };
+            for to in squares_of(targets) {
+                if pchar == 'P' && to.rank() == last_rank {
+                    moves.extend(PROMOTIONS.iter().map(|p| Move::with_promotion(from, to, *p)));
+                } else {
+                    moves.push(Move::new(from, to));
+                }
+            }
+            if pchar == 'K' {
+                moves.extend(self.castling_moves(from, side, occupied));
+            }
+        }
+        moves.retain(|mv| self.leaves_king_safe(mv));
+        moves
+    }
+
+    fn pawn_targets(&self, from: Square, side: Side, enemy: u64, occupied: u64) -> u64 {
+        let (forward, start_rank) = match side {
+            Side::White => (Direction::N, Rank::R2),
+            Side::Black => (Direction::S, Rank::R7),
+        };
+        let mut targets = 0u64;
+        if let Some(one) = from.offset(forward)
+            && occupied & (1u64 << one.index()) == 0
+        {
+            targets |= 1u64 << one.index();
+            if from.rank() == start_rank
+                && let Some(two) = one.offset(forward)
+                && occupied & (1u64 << two.index()) == 0
+            {
+                targets |= 1u64 << two.index();
+            }
+        }
+        let pawn = self.get_piece_on(from).and_then(|piece| piece.get_piece_data().ok());
+        let attacks = pawn.map_or(0, |data| piece_attacks(data, from, occupied));
+        let ep = self.en_passant_square().map_or(0, |square| 1u64 << square.index());
+        targets | (attacks & (enemy | ep))
+    }
+
+    fn castling_moves(&self, king: Square, side: Side, occupied: u64) -> Vec<Move> {
+        let rights = self.castling_rights();
+        let (home, rook, king_side, queen_side) = match side {
+            Side::White => (Square::e1, 'R', rights.white_king_side, rights.white_queen_side),
+            Side::Black => (Square::e8, 'r', rights.black_king_side, rights.black_queen_side),
+        };
+        if king != home || self.in_check() {
+            return Vec::new();
+        }
+        let rank = home.rank();
+        let at = |file: File| Square::from_file_rank(file, rank);
+        let mut moves = Vec::new();
+        // (right held, rook file, squares that must be empty, squares the king crosses)
+        let wings = [
+            (king_side, File::H, vec![File::F, File::G], [File::F, File::G]),
+            (queen_side, File::A, vec![File::B, File::C, File::D], [File::D, File::C]),
+        ];
+        for (held, rook_file, empty, crossed) in wings {
+            let clear = empty.iter().all(|file| occupied & (1u64 << at(*file).index()) == 0);
+            let safe = crossed.iter().all(|file| attackers_of_with(self, at(*file), side.opposite(), occupied) == 0);
+            if held && self.piece_char_on(at(rook_file)) == Some(rook) && clear && safe {
+                moves.push(Move::new(home, at(crossed[1])));
+            }
+        }
+        moves
+    }
+
+    /// Plays the move on the occupancy alone and asks whether the mover's king is then attacked.
+    fn leaves_king_safe(&self, mv: &Move) -> bool {
+        let side = self.turn();
+        let is_king = self.piece_char_on(mv.from).is_some_and(|c| c.eq_ignore_ascii_case(&'K'));
+        let Some(king) = (if is_king { Some(mv.to) } else { self.king_location(side) }) else { return true };
+
+        let to_bit = 1u64 << mv.to.index();
+        let mut occupied = (self.get_occupied_bitboard() & !(1u64 << mv.from.index())) | to_bit;
+        if self.is_en_passant_capture(mv) {
+            occupied &= !(1u64 << mv.to.en_passant_victim().index());
+        }
+        // a captured piece still has its bit set (the mover stands there), so mask it out
+        attackers_of_with(self, king, side.opposite(), occupied) & !to_bit == 0
+    }
+
+    fn is_en_passant_capture(&self, mv: &Move) -> bool {
+        self.en_passant_square() == Some(mv.to)
+            && self.piece_char_on(mv.from).is_some_and(|c| c.eq_ignore_ascii_case(&'P'))
+    }
+
+    pub fn is_capture(&self, mv: &Move) -> bool {
+        self.is_square_occupied(mv.to) || self.is_en_passant_capture(mv)
+    }
+
+    /// Plays a legal move, including castling and promotion, with full exchanger processing.
+    pub fn make_move(&self, mv: Move) -> Result<Board> {
+        if !self.legal_moves().contains(&mv) {
+            return Err(Error::IllegalMove {
+                from: mv.from,
+                to: mv.to,
+                reason: format!("{mv} is not legal for {:?}", self.turn()),
+            });
+        }
+        self.full_process(mv.from, mv.to, mv.promotion)
+    }
+
+    pub fn parse_uci(&self, uci: &str) -> Result<Move> {
+        let mv = Move::from_uci(uci)?;
+        if !self.legal_moves().contains(&mv) {
+            return Err(Error::InvalidNotation { notation: uci.to_string(), reason: "not a legal move".to_string() });
+        }
+        Ok(mv)
+    }
+
+    /// Resolves a SAN move against the legal moves. Check and annotation suffixes
+    /// ('+', '#', '!', '?') are accepted and ignored.
+    pub fn parse_san(&self, san: &str) -> Result<Move> {
+        let invalid = |reason: &str| Error::InvalidNotation { notation: san.to_string(), reason: reason.to_string() };
+        let body = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        let legal = self.legal_moves();
+
+        if matches!(body, "O-O" | "0-0" | "O-O-O" | "0-0-0") {
+            let king = self.king_location(self.turn()).ok_or_else(|| invalid("no king to castle"))?;
+            let file = if body.len() == 3 { File::G } else { File::C };
+            let mv = Move::new(king, Square::from_file_rank(file, king.rank()));
+            return if legal.contains(&mv) { Ok(mv) } else { Err(invalid("castling is not legal here")) };
+        }
+
+        let mut chars: Vec<char> = body.chars().collect();
+        let piece = match chars.first() {
+            Some(c @ ('K' | 'Q' | 'R' | 'B' | 'N')) => {
+                let c = *c;
+                chars.remove(0);
+                c
+            }
+            Some(_) => 'P',
+            None => return Err(invalid("empty move")),
+        };
+        let mut promotion = None;
+        if let Some(last) = chars.last().copied()
+            && PROMOTIONS.contains(&last)
+        {
+            promotion = Some(last);
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+        if chars.len() < 2 {
+            return Err(invalid("missing destination square"));
+        }
+        let to = Square::from_chars(chars[chars.len() - 2], chars[chars.len() - 1])
+            .ok_or_else(|| invalid("bad destination square"))?;
+        let mut qualifier: Vec<char> = chars[..chars.len() - 2].to_vec();
+        let capture = qualifier.last() == Some(&'x');
+        if capture {
+            qualifier.pop();
+        }
+        let (mut from_file, mut from_rank) = (None, None);
+        for c in qualifier {
+            match (File::from_char(c), Rank::from_char(c)) {
+                (Some(file), _) if from_file.is_none() => from_file = Some(file),
+                (_, Some(rank)) if from_rank.is_none() => from_rank = Some(rank),
+                _ => return Err(invalid("unexpected characters before the destination")),
+            }
+        }
+
+        let candidates: Vec<&Move> = legal
+            .iter()
+            .filter(|mv| {
+                mv.to == to
+                    && mv.promotion == promotion
+                    && self.piece_char_on(mv.from).map(|c| c.to_ascii_uppercase()) == Some(piece)
+                    && from_file.is_none_or(|file| mv.from.file() == file)
+                    && from_rank.is_none_or(|rank| mv.from.rank() == rank)
+            })
+            .collect();
+        match candidates[..] {
+            [mv] if capture && !self.is_capture(mv) => Err(invalid("marked as a capture but nothing is taken")),
+            [mv] => Ok(*mv),
+            [] => Err(invalid("no legal move matches")),
+            _ => Err(invalid("ambiguous, more than one legal move matches")),
+        }
+    }
+
+    /// Writes a legal move in SAN. The '+' or '#' suffix comes from the checks
+    /// recorded on the board after playing it.
+    pub fn to_san(&self, mv: Move) -> Result<String> {
+        self.san_and_play(mv).map(|(san, _)| san)
+    }
+
+    /// `to_san` together with the board it played to find the suffix, for callers
+    /// that go on from there.
+    pub(crate) fn san_and_play(&self, mv: Move) -> Result<(String, Board)> {
+        let after = self.make_move(mv)?;
+        let pchar = self.piece_char_on(mv.from).ok_or(Error::EmptySquare(mv.from))?.to_ascii_uppercase();
+
+        let mut san = String::new();
+        if pchar == 'K' && mv.from.file().index().abs_diff(mv.to.file().index()) == 2 {
+            san.push_str(if mv.to.file() == File::G { "O-O" } else { "O-O-O" });
+        } else if pchar == 'P' {
+            if self.is_capture(&mv) {
+                san.push(mv.from.file().to_char());
+                san.push('x');
+            }
+            san.push_str(mv.to.as_ref());
+            if let Some(promoted) = mv.promotion {
+                san.push('=');
+                san.push(promoted);
+            }
+        } else {
+            san.push(pchar);
+            let rivals: Vec<Move> = self
+                .legal_moves()
+                .into_iter()
+                .filter(|other| {
+                    other.to == mv.to
+                        && other.from != mv.from
+                        && self.piece_char_on(other.from).map(|c| c.to_ascii_uppercase()) == Some(pchar)
+                })
+                .collect();
+            if !rivals.is_empty() {
+                if rivals.iter().all(|other| other.from.file() != mv.from.file()) {
+                    san.push(mv.from.file().to_char());
+                } else if rivals.iter().all(|other| other.from.rank() != mv.from.rank()) {
+                    san.push(mv.from.rank().to_char());
+                } else {
+                    san.push_str(mv.from.as_ref());
+                }
+            }
+            if self.is_capture(&mv) {
+                san.push('x');
+            }
+            san.push_str(mv.to.as_ref());
+        }
+
+        if !after.checks().is_empty() {
+            san.push(if after.legal_moves().is_empty() { '#' } else { '+' });
+        }
+        Ok((san, after))
+    }
+
+    /// Parses and plays a SAN move.
+    pub fn play_san(&self, san: &str) -> Result<Board> {
+        let mv = self.parse_san(san)?;
+        self.make_move(mv)
+    }
+
+    /// Counts the leaf positions `depth` plies ahead, for checking move generation
+    /// against published totals.
+    pub fn perft(&self, depth: u32) -> u64 {
+        count_leaves(&self.without_exchangers(), depth)
+    }
+
+    /// `perft` split by first move, in `legal_moves` order. Empty at depth 0, where
+    /// no move is played.
+    pub fn perft_divide(&self, depth: u32) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        let stripped = self.without_exchangers();
+        stripped
+            .legal_moves()
+            .into_iter()
+            .filter_map(|mv| stripped.play_unprocessed(mv).ok().map(|child| (mv, count_leaves(&child, depth - 1))))
+            .collect()
+    }
+}
+
+fn count_leaves(board: &Board, depth: u32) -> u64 {
+    let moves = board.legal_moves();
+    if depth <= 1 {
+        return if depth == 0 { 1 } else { moves.len() as u64 };
+    }
+    moves
+        .into_iter()
+        .filter_map(|mv| board.play_unprocessed(mv).ok())
+        .map(|child| count_leaves(&child, depth - 1))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square::*;
+
+    #[test]
+    fn test_legal_move_counts() {
+        let board = Board::from_fen(crate::fen::STANDARD_FEN).unwrap();
+        assert_eq!(board.legal_moves().len(), 20);
+
+        // the pinned knight may not move, leaving only the four king steps
+        let pinned = Board::from_fen("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+        let targets: Vec<Square> = pinned.legal_moves().iter().map(|mv| mv.to).collect();
+        assert_eq!(targets, vec![d1, f1, d2, f2]);
+
+        let mate = Board::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(mate.is_checkmate());
+        let stalemate = Board::from_fen("7k/8/6QK/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(stalemate.is_stalemate());
+    }
+
+    #[test]
+    fn test_san_round_trips() {
+        let board = Board::from_fen("r3k2r/1P6/8/3pP3/8/1N3N2/8/R3K2R w KQkq d6 0 1").unwrap();
+        let cases = [
+            ("exd6", Move::new(e5, d6)),
+            ("Nbd4", Move::new(b3, d4)),
+            ("Nfd4", Move::new(f3, d4)),
+            ("O-O", Move::new(e1, g1)),
+            ("O-O-O", Move::new(e1, c1)),
+            ("bxa8=Q+", Move::with_promotion(b7, a8, 'Q')),
+            ("b8=N", Move::with_promotion(b7, b8, 'N')),
+        ];
+        for (san, mv) in cases {
+            assert_eq!(board.parse_san(san).unwrap(), mv, "{san}");
+            assert_eq!(board.to_san(mv).unwrap(), san);
+        }
+        // the e.p. capture removes the d5 pawn, the long castle moves the a1 rook
+        assert!(board.play_san("exd6").unwrap().get_piece_on(d5).is_none());
+        let castled = board.play_san("O-O-O").unwrap();
+        assert_eq!(castled.get_piece_on(d1).map(|p| p.get_piece_type_as_char()), Some('R'));
+        assert!(!castled.castling_rights().white_queen_side && castled.castling_rights().black_king_side);
+
+        assert!(matches!(board.parse_san("Nd4"), Err(Error::InvalidNotation { .. })));
+        assert!(matches!(board.parse_san("Nxd4"), Err(Error::InvalidNotation { .. })));
+        assert!(matches!(board.parse_san("b8"), Err(Error::InvalidNotation { .. })));
+        assert!(matches!(board.parse_san("Ke3"), Err(Error::InvalidNotation { .. })));
+    }
+
+    #[test]
+    fn test_mate_suffix_and_uci() {
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert_eq!(board.to_san(Move::new(a1, a8)).unwrap(), "Ra8#");
+
+        assert_eq!(Move::from_uci("e7e8q").unwrap(), Move::with_promotion(e7, e8, 'Q'));
+        assert_eq!(Move::with_promotion(e7, e8, 'Q').to_uci(), "e7e8q");
+        assert_eq!(Move::new(e2, e4).to_string(), "e2e4");
+        assert!(Move::from_uci("e7e8k").is_err());
+        assert!(Move::from_uci("e9e8").is_err());
+        assert!(board.parse_uci("a1a8").is_ok());
+        assert!(board.parse_uci("a1b2").is_err());
+    }
+
+    #[test]
+    fn test_perft_matches_published_counts() {
+        let start = Board::from_fen(crate::fen::STANDARD_FEN).unwrap();
+        assert_eq!(start.perft(3), 8_902);
+        assert_eq!(start.perft(0), 1);
+        assert!(start.perft_divide(0).is_empty());
+        // "kiwipete": castling, en passant and promotions all in play
+        let kiwipete = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(kiwipete.perft(2), 2_039);
+        let divided = kiwipete.perft_divide(2);
+        assert_eq!(divided.len(), 48);
+        assert_eq!(divided.iter().map(|(_, n)| n).sum::<u64>(), 2_039);
+    }
+}
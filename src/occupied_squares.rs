@@ -1,204 +1,131 @@
 // src/OccupiedSquare.rs
 use crate::board::{Square};
-use crate::compass_groups::{Direction, HALF_WINDS};
+use crate::compass_groups::{Direction, ALL_DIRECTIONS};
+use crate::magic_bitboards::first_blocker;
 use regex::Regex;
 use std::str::FromStr;
 
 pub static FILES: &str = "abcdefgh";
 pub static RANKS: &str = "12345678";
 
-// Function to convert a square string (e.g., "a1") to a bit position (0-63)
+// Function to convert a square (e.g., a1) to a bit position (0-63)
 pub fn square_to_bit(square: Square) -> u64 {
-    let sq = format!("{square}");
-    // let square_regex = Regex::new(r"^[a-h][1-8]$");
-    let file = sq.chars().next().unwrap() as u8 - b'a';
-    let rank = sq.chars().nth(1).unwrap() as u8 - b'1';
-
-    (rank * 8 + file) as u64
+    square.index() as u64
 }
 
 // Function to convert a bit position (0-63) back to a square string (e.g., "a1")
 pub fn bit_to_string_square(bit: u64) -> Option<String> {
-    if bit > 63 {
-        return None;
-    }
-
-    let file = (bit % 8) as u8 + b'a';
-    let rank = (bit / 8) as u8 + b'1';
-
-    Some(format!("{}{}", file as char, rank as char))
+    Square::from_index(bit as usize).map(|square| square.to_string())
 }
 
 pub fn print_ray_string(origin: &str, direction: Direction, ray: &str) {
     println!("{origin} {direction:?} ray: {ray}");
 }
 
-pub fn generate_ray_path(square: Square, direction: Direction, occupied: u64) -> Option<String> {
-
-    let mut empty_count: u8 = 0;
-    let mut path = String::new();
-
-    let start_bit = square_to_bit(square);
-    let mut current_bit = start_bit;
-    let (shift, edge_check): (i8, Box<dyn Fn(u64) -> bool>) = match direction {
-        Direction::N => (8, Box::new(|b| b <= 55)),
-        Direction::NNE => (17, Box::new(|b| b <= 46 && (b % 8) != 7)),
-        Direction::NE => (9, Box::new(|b| b <= 54 && (b % 8) != 7)),
-        Direction::ENE => (10, Box::new(|b| b <= 53 && (b % 8) < 6)),
-        Direction::E => (1, Box::new(|b| b <= 62 && (b % 8) != 7)),
-        Direction::ESE => (-6, Box::new(|b| b >= 8 && (b % 8) < 6)),
-        Direction::SE => (-7, Box::new(|b| b >= 8 && (b % 8) != 7)),
-        Direction::SSE => (-15, Box::new(|b| b >= 16 && (b % 8) != 7)),
-
-        // Direction::SSE => (-15, Box::new(|b| b >= 16 && (b % 8) < 8)),
-        Direction::S => (-8, Box::new(|b| b >= 8)),
-        Direction::SSW => (-17, Box::new(|b| b >= 17 && (b % 8) != 0)),
-        Direction::SW => (-9, Box::new(|b| b >= 8 && (b % 8) != 0)),
-        Direction::WSW => (-10, Box::new(|b| b >= 10 && (b % 8) > 1)),
-        Direction::W => (-1, Box::new(|b| (b % 8) != 0)),
-        Direction::WNW => (6, Box::new(|b| b <= 55 && (b % 8) > 1)),
-        Direction::NW => (7, Box::new(|b| b <= 55 && (b % 8) != 0)),
-        Direction::NNW => (15, Box::new(|b| b <= 47 && (b % 8) != 0)),
-    };
-
-    let mut underscore_available = true;
-    while edge_check(current_bit) {
-        let next_bit = (current_bit as i64 + shift as i64) as u64;
-        if (occupied & (1u64 << next_bit)) != 0 {
-            if empty_count > 0 && underscore_available {
-                path.push('_');
-                empty_count = 0;
-                underscore_available = false;
+/// Builds the neighbour table: the square one step away from each square in each direction.
+const fn build_neighbours() -> [[Option<Square>; 16]; 64] {
+    let mut table = [[None; 16]; 64];
+    let mut idx = 0;
+    while idx < 64 {
+        let mut d = 0;
+        while d < 16 {
+            let (file_step, rank_step) = ALL_DIRECTIONS[d].delta();
+            let file = (idx % 8) as i8 + file_step;
+            let rank = (idx / 8) as i8 + rank_step;
+            if file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                table[idx][d] = Square::from_index((rank * 8 + file) as usize);
             }
-            if let Some(square_string) = &bit_to_string_square(next_bit) {
-                path.push_str(square_string);
-                underscore_available = false;
+            d += 1;
+        }
+        idx += 1;
+    }
+    table
+}
+
+/// Builds the ray table from the neighbour table: every square along each ray,
+/// stopping after one step for the half winds.
+const fn build_rays() -> [[u64; 16]; 64] {
+    let mut table = [[0u64; 16]; 64];
+    let mut idx = 0;
+    while idx < 64 {
+        let mut d = 0;
+        while d < 16 {
+            let mut ray = 0u64;
+            let mut current = NEIGHBOURS[idx][d];
+            while let Some(sq) = current {
+                ray |= 1u64 << sq.index();
+                if ALL_DIRECTIONS[d].is_half_wind() {
+                    break;
+                }
+                current = NEIGHBOURS[sq.index()][d];
             }
-        } else {
-            empty_count += 1;
+            table[idx][d] = ray;
+            d += 1;
         }
+        idx += 1;
+    }
+    table
+}
 
-        current_bit = next_bit;
+pub static NEIGHBOURS: [[Option<Square>; 16]; 64] = build_neighbours();
+pub static RAYS: [[u64; 16]; 64] = build_rays();
 
-        if HALF_WINDS.contains(&direction) {
-            break;
-        }
+// Leading '_' marks a first occupant that is not adjacent, so only sliders can reach it.
+fn push_first_occupant(path: &mut String, square: Square, direction: Direction, first: Square) {
+    if square.offset(direction) != Some(first) {
+        path.push('_');
     }
+    path.push_str(first.as_ref());
+}
 
-    if !path.is_empty() { Some(path) } else { None }
+pub fn generate_ray_path(square: Square, direction: Direction, occupied: u64) -> Option<String> {
+    let first = first_blocker(square, direction, occupied)?;
+    let mut path = String::new();
+    push_first_occupant(&mut path, square, direction, first);
+
+    // peel each blocker off in turn so the next lookup yields its x-ray continuation
+    let mut remaining = occupied & !(1u64 << first.index());
+    while let Some(next) = first_blocker(square, direction, remaining) {
+        path.push_str(next.as_ref());
+        remaining &= !(1u64 << next.index());
+    }
+    Some(path)
 }
 
 pub fn first_occpd_square (square: Square, direction: Direction, occupied: u64) -> Option<String> {
-    // let origin = format!("{square}");
+    let first = first_blocker(square, direction, occupied)?;
     let mut path = String::new();
-    let mut empty_count: u8 = 0;
-    let mut underscore_available = true;
-    let start_bit = square_to_bit(square);
-    let mut current_bit = start_bit;
-    let (shift, edge_check): (i8, Box<dyn Fn(u64) -> bool>) = match direction {
-        Direction::N => (8, Box::new(|b| b <= 55)),
-        Direction::NNE => (17, Box::new(|b| b <= 46 && (b % 8) != 7)),
-        Direction::NE => (9, Box::new(|b| b <= 54 && (b % 8) != 7)),
-        Direction::ENE => (10, Box::new(|b| b <= 53 && (b % 8) < 6)),
-        Direction::E => (1, Box::new(|b| b <= 62 && (b % 8) != 7)),
-        Direction::ESE => (-6, Box::new(|b| b >= 8 && (b % 8) < 6)),
-        Direction::SE => (-7, Box::new(|b| b >= 8 && (b % 8) != 7)),
-        Direction::SSE => (-15, Box::new(|b| b >= 16 && (b % 8) != 7)),
-
-        // Direction::SSE => (-15, Box::new(|b| b >= 16 && (b % 8) < 8)),
-        Direction::S => (-8, Box::new(|b| b >= 8)),
-        Direction::SSW => (-17, Box::new(|b| b >= 17 && (b % 8) != 0)),
-        Direction::SW => (-9, Box::new(|b| b >= 8 && (b % 8) != 0)),
-        Direction::WSW => (-10, Box::new(|b| b >= 10 && (b % 8) > 1)),
-        Direction::W => (-1, Box::new(|b| (b % 8) != 0)),
-        Direction::WNW => (6, Box::new(|b| b <= 55 && (b % 8) > 1)),
-        Direction::NW => (7, Box::new(|b| b <= 55 && (b % 8) != 0)),
-        Direction::NNW => (15, Box::new(|b| b <= 47 && (b % 8) != 0)),
-    };
-
-    while edge_check(current_bit) {
-        let next_bit = (current_bit as i64 + shift as i64) as u64;
-        if (occupied & (1u64 << next_bit)) != 0 {
-            if empty_count > 0 && underscore_available {
-                path.push('_');
-                empty_count = 0;
-                underscore_available = false;
-            }
-            if let Some(sq) = &bit_to_string_square(next_bit) {
-                path.push_str(sq);
-                return Some(path);
-            }
-        } else {
-            empty_count += 1;
-        }
-        current_bit = next_bit;
-        if HALF_WINDS.contains(&direction) {
-            break;
-        }
-    }
-    None
+    push_first_occupant(&mut path, square, direction, first);
+    Some(path)
 }
 
-// pub fn get_next_sqid(osqid: &str, drctn: Direction) -> Option<String> {
 pub fn get_next_sqid(orig_sqid: Square, drctn: Direction) -> Option<String> {
-    let osqid = orig_sqid.to_string();
-    if osqid.len() != 2 {
-        return None;
-    }
-
-    let sqid_file = osqid.chars().next().expect("Expected a file character");
-    let sqid_rank = osqid.chars().nth(1).expect("Expected a rank character");
-
-    let calc_sqid =
-        |rlmt_opt: Option<i8>, flmt_opt: Option<i8>, roffset: i8, foffset: i8| -> Option<String> {
-            let mut rank = sqid_rank; // default rank
-            let mut file = sqid_file; // default file
-            if let Some(rlmt) = rlmt_opt {
-                let ridx_opt: Option<usize> = RANKS.find(sqid_rank);
-                let ridx: i8 = ridx_opt.unwrap() as i8;
-                if ridx == rlmt {
-                    return None; // too close to edge of board
-                }
-                rank = RANKS.chars().nth((ridx + roffset) as usize).unwrap();
-            } // None of rlmt -> use default rank
-
-            if let Some(flmt) = flmt_opt {
-                let fidx_opt: Option<usize> = FILES.find(sqid_file);
-                let fidx: i8 = fidx_opt.unwrap() as i8;
-                if fidx == flmt {
-                    return None; // too close to edge of board
-                }
-                file = FILES.chars().nth((fidx + foffset) as usize).unwrap();
-            } // None of flmt -> use default file
-
-            let sqid = [file, rank];
-            Some(sqid.iter().collect())
-        };
-
-    match drctn {
-        Direction::N => calc_sqid(Some(7), None, 1, 0),
-        Direction::NNE => calc_sqid(Some(6), Some(7), 2, 1),
-        Direction::NE => calc_sqid(Some(7), Some(7), 1, 1),
-        Direction::ENE => calc_sqid(Some(7), Some(6), 1, 2),
-        Direction::E => calc_sqid(None, Some(7), 0, 1),
-        Direction::ESE => calc_sqid(Some(0), Some(6), -1, 2),
-        Direction::SE => calc_sqid(Some(0), Some(7), -1, 1),
-        Direction::SSE => calc_sqid(Some(1), Some(7), -2, 1),
-        Direction::S => calc_sqid(Some(0), None, -1, 0),
-        Direction::SSW => calc_sqid(Some(1), Some(0), -2, -1),
-        Direction::SW => calc_sqid(Some(0), Some(0), -1, -1),
-        Direction::WSW => calc_sqid(Some(0), Some(1), -1, -2),
-        Direction::W => calc_sqid(None, Some(0), 0, -1),
-        Direction::WNW => calc_sqid(Some(7), Some(1), 1, -2),
-        Direction::NW => calc_sqid(Some(7), Some(0), 1, -1),
-        Direction::NNW => calc_sqid(Some(6), Some(0), 2, -1),
-    }
+    orig_sqid.offset(drctn).map(|sq| sq.to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::compass_groups::Direction;
+    use crate::compass_groups::{Direction, HALF_WINDS};
+
+    #[test]
+    fn test_slider_ray_paths() {
+        let mut occupied = 0u64;
+        for square in [Square::d4, Square::d6, Square::d8, Square::b2, Square::g4] {
+            occupied |= 1u64 << square.index();
+        }
+
+        assert_eq!(generate_ray_path(Square::d4, Direction::N, occupied), Some("_d6d8".to_string()));
+        assert_eq!(first_occpd_square(Square::d4, Direction::N, occupied), Some("_d6".to_string()));
+        assert_eq!(generate_ray_path(Square::d4, Direction::SW, occupied), Some("_b2".to_string()));
+        assert_eq!(generate_ray_path(Square::d4, Direction::E, occupied), Some("_g4".to_string()));
+        assert_eq!(generate_ray_path(Square::d6, Direction::S, occupied), Some("_d4".to_string()));
+        assert_eq!(generate_ray_path(Square::d6, Direction::N, occupied), Some("_d8".to_string()));
+        assert_eq!(generate_ray_path(Square::d8, Direction::S, occupied), Some("_d6d4".to_string()));
+        assert_eq!(generate_ray_path(Square::d4, Direction::W, occupied), None);
+        assert_eq!(Square::h4.offset(Direction::E), None);
+        assert_eq!(Square::g1.offset(Direction::NNE), Some(Square::h3));
+    }
 
     #[test]
     fn test_piece_moves() {
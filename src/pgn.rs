@@ -0,0 +1,675 @@
+// src/pgn.rs
+// Portable Game Notation reader and writer. Games keep their tags, comments, NAGs
+// and variations; `PgnGame::replay` plays the mainline through `Board::make_move`.
+use crate::board::Board;
+use crate::error::{Error, Result};
+use crate::fen::STANDARD_FEN;
+use crate::pieces::Side;
+use regex::Regex;
+use std::fmt;
+use std::sync::LazyLock as Lazy;
+
+/// The tags every exported game carries, in the order the standard gives them.
+pub const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// Export lines stay under 80 columns.
+const LINE_WIDTH: usize = 79;
+
+static COMMAND: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[%(eval|clk)\s+([^\]]+)\]").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// "*": unfinished, abandoned or unknown.
+    Ongoing,
+}
+
+impl GameResult {
+    pub fn from_pgn(token: &str) -> Option<GameResult> {
+        match token {
+            "1-0" => Some(GameResult::WhiteWins),
+            "0-1" => Some(GameResult::BlackWins),
+            "1/2-1/2" => Some(GameResult::Draw),
+            "*" => Some(GameResult::Ongoing),
+            _ => None,
+        }
+    }
+
+    pub fn as_pgn(&self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_pgn())
+    }
+}
+
+/// An engine score as carried by a `[%eval ...]` comment command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Evaluation {
+    /// From White's point of view, written in pawns: `[%eval -1.35]`.
+    Centipawns(i32),
+    /// Mate in so many moves, negative when Black mates: `[%eval #-3]`.
+    Mate(i32),
+}
+
+impl Evaluation {
+    pub fn from_pgn(text: &str) -> Option<Evaluation> {
+        match text.strip_prefix('#') {
+            Some(moves) => moves.parse().ok().map(Evaluation::Mate),
+            None => text.parse::<f64>().ok().map(|pawns| Evaluation::Centipawns((pawns * 100.0).round() as i32)),
+        }
+    }
+
+    pub fn to_pgn(&self) -> String {
+        match self {
+            Evaluation::Centipawns(cp) => format!("{:.2}", *cp as f64 / 100.0),
+            Evaluation::Mate(moves) => format!("#{moves}"),
+        }
+    }
+}
+
+/// Reads the h:mm:ss of a `[%clk ...]` command as whole seconds.
+fn parse_clock(text: &str) -> Option<u32> {
+    let mut seconds = 0u32;
+    for part in text.split(':') {
+        let whole = part.split('.').next()?;
+        seconds = seconds.checked_mul(60)?.checked_add(whole.parse().ok()?)?;
+    }
+    Some(seconds)
+}
+
+fn format_clock(seconds: u32) -> String {
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PgnMove {
+    pub san: String,
+    /// Numeric annotation glyphs; suffixes such as "!?" are stored as their NAG.
+    pub nags: Vec<u8>,
+    /// A comment that opens the line before this move.
+    pub comment_before: Option<String>,
+    pub comments: Vec<String>,
+    /// Taken from, and written back as, a `[%eval ...]` comment command.
+    pub eval: Option<Evaluation>,
+    /// Clock time left after the move in seconds, from `[%clk ...]`.
+    pub clock: Option<u32>,
+    /// Alternatives to this move, each a line starting in the same position.
+    pub variations: Vec<Vec<PgnMove>>,
+    /// Source line of the SAN token, for error reports.
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnGame {
+    /// Tag pairs in the order they were read.
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnMove>,
+    pub result: GameResult,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// The position the movetext starts from: the FEN tag if there is one,
+    /// otherwise the standard position.
+    pub fn start_board(&self) -> Result<Board> {
+        match self.tag("FEN") {
+            Some(fen) => Board::from_fen(fen),
+            None => Board::from_fen(crate::fen::STANDARD_FEN),
+        }
+    }
+
+    /// Plays the mainline one ply at a time, yielding the board after each move.
+    /// The first illegal or unreadable move ends the replay with an error.
+    pub fn replay(&self) -> Replay<'_> {
+        Replay { moves: self.moves.iter(), board: Some(self.start_board()), ply: 0 }
+    }
+}
+
+pub struct Replay<'a> {
+    moves: std::slice::Iter<'a, PgnMove>,
+    board: Option<Result<Board>>,
+    ply: usize,
+}
+
+impl Iterator for Replay<'_> {
+    type Item = Result<Board>;
+
+    fn next(&mut self) -> Option<Result<Board>> {
+        let board = match self.board.take()? {
+            Ok(board) => board,
+            Err(err) => return Some(Err(err)),
+        };
+        let mv = self.moves.next()?;
+        self.ply += 1;
+        let next = board.play_san(&mv.san).map_err(|err| Error::InvalidPgn {
+            line: mv.line,
+            reason: format!("ply {} '{}': {err}", self.ply, mv.san),
+        });
+        if let Ok(next) = &next {
+            self.board = Some(Ok(next.clone()));
+        }
+        Some(next)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    Open,
+    Close,
+    Result(GameResult),
+    San(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let mut at_line_start = true;
+    let fail = |line: usize, reason: &str| Error::InvalidPgn { line, reason: reason.to_string() };
+
+    while let Some(c) = chars.next() {
+        let starts_line = at_line_start;
+        at_line_start = c == '\n';
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            // escape mechanism: the whole line is ignored
+            '%' if starts_line => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        at_line_start = true;
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|c| *c != '\n').collect();
+                tokens.push((Token::Comment(comment.trim().to_string()), line));
+                line += 1;
+                at_line_start = true;
+            }
+            '{' => {
+                let start = line;
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            comment.push(c);
+                        }
+                        None => return Err(fail(start, "unterminated comment")),
+                    }
+                }
+                let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+                tokens.push((Token::Comment(comment), start));
+            }
+            '[' => {
+                let body: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                let (name, rest) = body.trim().split_once(char::is_whitespace).ok_or_else(|| fail(line, "tag without a value"))?;
+                let rest = rest.trim();
+                if !(rest.len() >= 2 && rest.starts_with('"') && rest.ends_with('"')) {
+                    return Err(fail(line, "tag value must be quoted"));
+                }
+                let value = rest[1..rest.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\");
+                tokens.push((Token::Tag(name.to_string(), value), line));
+            }
+            '(' => tokens.push((Token::Open, line)),
+            ')' => tokens.push((Token::Close, line)),
+            '$' => {
+                let mut digits = String::new();
+                while let Some(d) = chars.next_if(|d| d.is_ascii_digit()) {
+                    digits.push(d);
+                }
+                let nag = digits.parse().map_err(|_| fail(line, "'$' must be followed by a number"))?;
+                tokens.push((Token::Nag(nag), line));
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"{}()[];$".contains(*c)) {
+                    word.push(c);
+                }
+                tokens.extend(classify_word(&word).into_iter().map(|token| (token, line)));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Splits a movetext word into a SAN move and its suffix annotation, dropping move numbers.
+fn classify_word(word: &str) -> Vec<Token> {
+    if let Some(result) = GameResult::from_pgn(word) {
+        return vec![Token::Result(result)];
+    }
+    // "12." and "12..." are move numbers; "12.e4" carries its move along
+    let digits = word.len() - word.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let word = if word[digits..].starts_with('.') { word[digits..].trim_start_matches('.') } else { word };
+    if word.is_empty() {
+        return Vec::new();
+    }
+    let san = word.trim_end_matches(['!', '?']);
+    let nag = match &word[san.len()..] {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+    let mut tokens = vec![Token::San(san.to_string())];
+    tokens.extend(nag.map(Token::Nag));
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens.get(self.pos).or(self.tokens.last()).map_or(1, |(_, line)| *line)
+    }
+
+    fn game(&mut self) -> Result<Option<PgnGame>> {
+        let mut tags = Vec::new();
+        while let Some(Token::Tag(name, value)) = self.peek() {
+            tags.push((name.clone(), value.clone()));
+            self.pos += 1;
+        }
+        if tags.is_empty() && self.peek().is_none() {
+            return Ok(None);
+        }
+        let moves = self.line_of_moves(0)?;
+        let result = match self.peek() {
+            Some(Token::Result(result)) => {
+                let result = *result;
+                self.pos += 1;
+                result
+            }
+            // a missing result is tolerated when the next game or the input begins
+            Some(Token::Tag(..)) | None => GameResult::Ongoing,
+            Some(token) => {
+                return Err(Error::InvalidPgn { line: self.line(), reason: format!("unexpected {token:?}") });
+            }
+        };
+        Ok(Some(PgnGame { tags, moves, result }))
+    }
+
+    fn line_of_moves(&mut self, depth: usize) -> Result<Vec<PgnMove>> {
+        let mut moves: Vec<PgnMove> = Vec::new();
+        let mut pending_comment: Option<String> = None;
+        loop {
+            let line = self.line();
+            match self.peek().cloned() {
+                Some(Token::San(san)) => {
+                    moves.push(PgnMove { san, comment_before: pending_comment.take(), line, ..PgnMove::default() });
+                }
+                Some(Token::Comment(text)) => match moves.last_mut() {
+                    Some(last) => {
+                        for command in COMMAND.captures_iter(&text) {
+                            match &command[1] {
+                                "eval" => last.eval = Evaluation::from_pgn(command[2].trim()),
+                                _ => last.clock = parse_clock(command[2].trim()),
+                            }
+                        }
+                        let rest = COMMAND.replace_all(&text, "");
+                        let rest = rest.split_whitespace().collect::<Vec<_>>().join(" ");
+                        if !rest.is_empty() {
+                            last.comments.push(rest);
+                        }
+                    }
+                    None => {
+                        pending_comment = Some(match pending_comment.take() {
+                            Some(earlier) => format!("{earlier} {text}"),
+                            None => text,
+                        })
+                    }
+                },
+                Some(Token::Nag(nag)) => {
+                    let last = moves.last_mut().ok_or(Error::InvalidPgn { line, reason: "NAG before any move".to_string() })?;
+                    last.nags.push(nag);
+                }
+                Some(Token::Open) => {
+                    self.pos += 1;
+                    let variation = self.line_of_moves(depth + 1)?;
+                    if self.peek() != Some(&Token::Close) {
+                        return Err(Error::InvalidPgn { line, reason: "unclosed variation".to_string() });
+                    }
+                    let last = moves.last_mut().ok_or(Error::InvalidPgn {
+                        line,
+                        reason: "variation before any move".to_string(),
+                    })?;
+                    last.variations.push(variation);
+                }
+                Some(Token::Close) if depth > 0 => return Ok(moves),
+                Some(Token::Close) => {
+                    return Err(Error::InvalidPgn { line, reason: "')' without a matching '('".to_string() });
+                }
+                Some(Token::Result(_)) | Some(Token::Tag(..)) | None => {
+                    if depth > 0 {
+                        return Err(Error::InvalidPgn { line, reason: "unclosed variation".to_string() });
+                    }
+                    return Ok(moves);
+                }
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+impl PgnGame {
+    /// The move number and side to move of the first ply, from the FEN tag if present.
+    fn first_ply(&self) -> (u32, Side) {
+        let Some(fen) = self.tag("FEN") else { return (1, Side::White) };
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let side = if fields.get(1) == Some(&"b") { Side::Black } else { Side::White };
+        (fields.get(5).and_then(|n| n.parse().ok()).unwrap_or(1), side)
+    }
+
+    /// Writes the game as export-format PGN: the Seven Tag Roster first ("?" where
+    /// unknown), then any other tags, then movetext wrapped below 80 columns.
+    pub fn to_pgn(&self) -> String {
+        let mut out = String::new();
+        let escape = |value: &str| value.replace('\\', "\\\\").replace('"', "\\\"");
+        for name in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => self.result.as_pgn(),
+                "Date" => self.tag(name).unwrap_or("????.??.??"),
+                _ => self.tag(name).unwrap_or("?"),
+            };
+            out.push_str(&format!("[{name} \"{}\"]\n", escape(value)));
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+                out.push_str(&format!("[{name} \"{}\"]\n", escape(value)));
+            }
+        }
+        out.push('\n');
+
+        let mut tokens = Vec::new();
+        let (number, side) = self.first_ply();
+        movetext_tokens(&self.moves, number, side, &mut tokens);
+        tokens.push(self.result.as_pgn().to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                out.push_str(&line);
+                out.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        out.push_str(&line);
+        out.push('\n');
+        out
+    }
+}
+
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_pgn())
+    }
+}
+
+/// A comment split into words, so long comments can wrap like the rest of the movetext.
+fn comment_tokens(text: &str, tokens: &mut Vec<String>) {
+    let mut words: Vec<String> = text.split_whitespace().map(str::to_string).collect();
+    if words.is_empty() {
+        words.push(String::new());
+    }
+    words[0].insert(0, '{');
+    words.last_mut().expect("at least one word").push('}');
+    tokens.extend(words);
+}
+
+fn movetext_tokens(moves: &[PgnMove], mut number: u32, mut side: Side, tokens: &mut Vec<String>) {
+    // black's moves need "N..." whenever something interrupts the move pair
+    let mut interrupted = true;
+    for mv in moves {
+        if let Some(comment) = &mv.comment_before {
+            comment_tokens(comment, tokens);
+            interrupted = true;
+        }
+        match side {
+            Side::White => tokens.push(format!("{number}.")),
+            Side::Black if interrupted => tokens.push(format!("{number}...")),
+            Side::Black => {}
+        }
+        tokens.push(mv.san.clone());
+        tokens.extend(mv.nags.iter().map(|nag| format!("${nag}")));
+        interrupted = false;
+
+        let mut parts: Vec<String> = Vec::new();
+        if let Some(eval) = mv.eval {
+            parts.push(format!("[%eval {}]", eval.to_pgn()));
+        }
+        if let Some(clock) = mv.clock {
+            parts.push(format!("[%clk {}]", format_clock(clock)));
+        }
+        parts.extend(mv.comments.iter().cloned());
+        if !parts.is_empty() {
+            comment_tokens(&parts.join(" "), tokens);
+            interrupted = true;
+        }
+
+        for variation in &mv.variations {
+            let mut inner = Vec::new();
+            movetext_tokens(variation, number, side, &mut inner);
+            if inner.is_empty() {
+                continue;
+            }
+            inner[0].insert(0, '(');
+            inner.last_mut().expect("not empty").push(')');
+            tokens.extend(inner);
+            interrupted = true;
+        }
+
+        if side == Side::Black {
+            number += 1;
+        }
+        side = side.opposite();
+    }
+}
+
+impl Board {
+    /// Turns the moves played since `start` into a game, SAN movetext and all. The
+    /// result is read off the final position, and a non-standard start gets FEN tags.
+    /// `start` must be an earlier position of this game.
+    pub fn to_pgn_game(&self, start: &Board) -> Result<PgnGame> {
+        let played = self.moves().strip_prefix(start.moves()).ok_or_else(|| Error::InvalidPgn {
+            line: 0,
+            reason: "the start position is not an earlier position of this game".to_string(),
+        })?;
+        let mut board = start.clone();
+        let mut moves = Vec::new();
+        for mv in played {
+            let (san, after) = board.san_and_play(*mv)?;
+            moves.push(PgnMove { san, ..PgnMove::default() });
+            board = after;
+        }
+
+        let result = if board.is_checkmate() {
+            if board.turn() == Side::White { GameResult::BlackWins } else { GameResult::WhiteWins }
+        } else if board.is_stalemate() {
+            GameResult::Draw
+        } else {
+            GameResult::Ongoing
+        };
+        let mut tags = Vec::new();
+        let start_fen = start.to_fen();
+        if start_fen != STANDARD_FEN {
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push(("FEN".to_string(), start_fen));
+        }
+        Ok(PgnGame { tags, moves, result })
+    }
+}
+
+/// Reads every game in a PGN collection.
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>> {
+    let mut parser = Parser { tokens: tokenize(text)?, pos: 0 };
+    let mut games = Vec::new();
+    while let Some(game) = parser.game()? {
+        games.push(game);
+    }
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPERA_GAME: &str = r#"[Event "Paris"]
+[Site "Paris FRA"]
+[Date "1858.??.??"]
+[White "Paul Morphy"]
+[Black "Duke Karl / Count Isouard"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 d6 3. d4 Bg4 $2 {This is a weak move already.} 4. dxe5 Bxf3 5. Qxf3 dxe5
+6. Bc4 Nf6 7. Qb3 Qe7 8. Nc3 (8. Qxb7 Qb4+ 9. Qxb4 Bxb4+) c6 9. Bg5 b5?! 10. Nxb5 cxb5
+11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7 Rxd7 14. Rd1 Qe6 15. Bxd7+ Nxd7 ; the queen sacrifice
+16. Qb8+!! Nxb8 17. Rd8# 1-0
+
+[Event "Fragment"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"]
+
+{Starts mid-game.} 1. e4 (1. e3 Kd7 (1... Ke7)) 1... Kd8 *
+"#;
+
+    #[test]
+    fn test_parse_tags_annotations_and_variations() {
+        let games = parse_pgn(OPERA_GAME).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let opera = &games[0];
+        assert_eq!(opera.tag("White"), Some("Paul Morphy"));
+        assert_eq!(opera.result, GameResult::WhiteWins);
+        assert_eq!(opera.moves.len(), 33);
+        assert_eq!(opera.moves[5].san, "Bg4");
+        assert_eq!(opera.moves[5].nags, vec![2]);
+        assert_eq!(opera.moves[5].comments, vec!["This is a weak move already.".to_string()]);
+        assert_eq!(opera.moves[14].variations[0].len(), 4);
+        assert_eq!(opera.moves[17].nags, vec![6]);
+        assert_eq!(opera.moves[29].comments, vec!["the queen sacrifice".to_string()]);
+        assert_eq!(opera.moves[30].san, "Qb8+");
+        assert_eq!(opera.moves[30].nags, vec![3]);
+
+        let fragment = &games[1];
+        assert_eq!(fragment.result, GameResult::Ongoing);
+        assert_eq!(fragment.moves[0].comment_before.as_deref(), Some("Starts mid-game."));
+        let nested = &fragment.moves[0].variations[0];
+        assert_eq!(nested[1].variations[0][0].san, "Ke7");
+    }
+
+    #[test]
+    fn test_replay_yields_a_board_per_ply() {
+        let games = parse_pgn(OPERA_GAME).unwrap();
+        let boards: Vec<Board> = games[0].replay().collect::<Result<_>>().unwrap();
+        assert_eq!(boards.len(), 33);
+        let last = boards.last().unwrap();
+        assert_eq!(last.to_fen(), "1n1Rkb1r/p4ppp/4q3/4p1B1/4P3/8/PPP2PPP/2K5 b k - 1 17");
+        assert!(last.is_checkmate());
+
+        let fragment: Vec<Board> = games[1].replay().collect::<Result<_>>().unwrap();
+        assert_eq!(fragment[1].to_fen(), "3k4/8/8/8/4P3/8/8/4K3 w - - 1 2");
+    }
+
+    #[test]
+    fn test_replay_and_parse_errors_name_the_line() {
+        let bad_move = parse_pgn("1. e4 e5 2. Ke3 *").unwrap();
+        let errors: Vec<Result<Board>> = bad_move[0].replay().collect();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[2], Err(Error::InvalidPgn { line: 1, reason }) if reason.starts_with("ply 3 'Ke3'")));
+
+        assert!(matches!(parse_pgn("1. e4 (1. d4 *"), Err(Error::InvalidPgn { .. })));
+        assert!(matches!(parse_pgn("1. e4 {never closed"), Err(Error::InvalidPgn { line: 1, .. })));
+        assert!(matches!(parse_pgn("[Event]\n1. e4 *"), Err(Error::InvalidPgn { line: 1, .. })));
+    }
+
+    #[test]
+    fn test_export_from_board_history() {
+        let start = Board::from_fen(STANDARD_FEN).unwrap();
+        let end = ["e4", "e5", "Nf3", "Nc6"].iter().fold(start.clone(), |board, san| board.play_san(san).unwrap());
+        let mut game = end.to_pgn_game(&start).unwrap();
+        game.tags.push(("White".to_string(), "neetroc".to_string()));
+        game.tags.push(("Event".to_string(), "Casual".to_string()));
+        game.moves[1].eval = Some(Evaluation::Centipawns(25));
+        game.moves[1].clock = Some(300);
+        game.moves[2].variations.push(vec![
+            PgnMove { san: "Bc4".to_string(), ..PgnMove::default() },
+            PgnMove { san: "Nf6".to_string(), ..PgnMove::default() },
+        ]);
+
+        let expected = "[Event \"Casual\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n\
+                        [White \"neetroc\"]\n[Black \"?\"]\n[Result \"*\"]\n\n\
+                        1. e4 e5 {[%eval 0.25] [%clk 0:05:00]} 2. Nf3 (2. Bc4 Nf6) 2... Nc6 *\n";
+        assert_eq!(game.to_pgn(), expected);
+
+        let reread = &parse_pgn(expected).unwrap()[0];
+        assert_eq!(reread.moves[1].eval, Some(Evaluation::Centipawns(25)));
+        assert_eq!(reread.moves[1].clock, Some(300));
+        assert!(reread.moves[1].comments.is_empty());
+        assert_eq!(reread.to_pgn(), expected);
+
+        let elsewhere = start.play_san("d4").unwrap();
+        assert!(matches!(end.to_pgn_game(&elsewhere), Err(Error::InvalidPgn { line: 0, .. })));
+        assert!(matches!(start.to_pgn_game(&end), Err(Error::InvalidPgn { line: 0, .. })));
+    }
+
+    #[test]
+    fn test_export_wraps_and_round_trips() {
+        let opera = &parse_pgn(OPERA_GAME).unwrap()[0];
+        let text = opera.to_pgn();
+        assert!(text.lines().all(|line| line.len() < 80));
+        assert!(text.contains("8. Nc3 (8. Qxb7 Qb4+ 9. Qxb4 Bxb4+) 8... c6"));
+
+        let reread = &parse_pgn(&text).unwrap()[0];
+        assert_eq!(reread.to_pgn(), text);
+        let last = reread.replay().last().unwrap().unwrap();
+        assert_eq!(last.to_fen(), "1n1Rkb1r/p4ppp/4q3/4p1B1/4P3/8/PPP2PPP/2K5 b k - 1 17");
+    }
+
+    #[test]
+    fn test_export_from_set_up_position() {
+        let start = Board::from_fen("8/1P2k3/8/8/8/8/8/4K3 b - - 0 12").unwrap();
+        let end = start.play_san("Kd6").unwrap().play_san("b8=N").unwrap();
+        assert_eq!(end.moves().last().unwrap().promotion, Some('N'));
+
+        let text = end.to_pgn_game(&start).unwrap().to_pgn();
+        assert!(text.contains("[SetUp \"1\"]\n[FEN \"8/1P2k3/8/8/8/8/8/4K3 b - - 0 12\"]\n"));
+        assert!(text.ends_with("\n12... Kd6 13. b8=N *\n"));
+        let replayed = parse_pgn(&text).unwrap()[0].replay().last().unwrap().unwrap();
+        assert_eq!(replayed.to_fen(), end.to_fen());
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt;
 use std::sync::OnceLock;
 use regex::Regex;
+use crate::board::Square;
+use crate::error::Error;
 use crate::pieces::PieceType;
 use crate::pieces::PieceTypeData;
 use crate::pieces::Side;
@@ -20,14 +22,9 @@ fn code_regex() -> &'static Regex {
 
 impl Pid {
     // 3. Update the constructor to use the regex for validation.
-    pub fn new(s: &str) -> Result<Self, String> {
+    pub fn new(s: &str) -> Result<Self, Error> {
         if !code_regex().is_match(s) {
-            return Err(format!("'{}' is not a valid 3-letter uppercase code.", s));
-        }
-
-        // Validate length is exactly 3
-        if s.len() != 3 {
-            return Err(format!("'{}' must be exactly 3 characters long.", s));
+            return Err(Error::InvalidPid(s.to_string()));
         }
 
         Ok(Self(s.to_string()))
@@ -45,27 +42,31 @@ impl Pid {
 
 
     pub fn get_square(&self) -> &str {
-        let sq = &self.0[0..2];
-        sq
+        &self.0[0..2]
+    }
+
+    // The regex guarantees three ASCII characters: file, rank and piece type.
+    pub fn square(&self) -> Square {
+        let bytes = self.0.as_bytes();
+        Square::from_chars(bytes[0] as char, bytes[1] as char).unwrap()
+    }
+
+    pub fn piece_char(&self) -> char {
+        self.0.as_bytes()[2] as char
     }
 
     pub fn get_side(self) -> Side {
-        let piece_char = &self.0.chars().nth(2).unwrap();
-        if piece_char.is_uppercase() {
+        if self.piece_char().is_uppercase() {
             Side::White
         } else {
             Side::Black
         }
     }
 
-    pub fn get_piece_data(self) -> &'static PieceTypeData {
-        let piece_type_char = self.0.chars().nth(2).unwrap();
-        let piece_type_ref = PieceType::get_piece_type(piece_type_char);
-        if let Some(piece_type) = piece_type_ref {
-            piece_type.get_data()
-        } else {
-            panic!("Invalid piece type")
-        }
+    pub fn get_piece_data(&self) -> Result<&'static PieceTypeData, Error> {
+        PieceType::get_piece_type(self.piece_char())
+            .map(PieceType::get_data)
+            .ok_or_else(|| Error::InvalidPid(self.0.clone()))
     }
 }
 
@@ -74,4 +75,23 @@ impl fmt::Display for Pid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
     }
-}
\ No newline at end of file
+}
+
+/// Splits an exchanger chain such as "e7rh7Q", or a pin chain such as "<e2Ne7r",
+/// into its pids, returning the leading '<' or '>' marker if there is one. A chain
+/// that is empty or holds anything but whole pids is reported, not cut short.
+pub fn split_chain(chain: &str) -> Result<(Option<char>, Vec<Pid>), Error> {
+    let (marker, pids) = match chain.strip_prefix(['<', '>']) {
+        Some(rest) => (chain.chars().next(), rest),
+        None => (None, chain),
+    };
+    let malformed = || Error::InconsistentExchangers(format!("malformed exchanger chain '{chain}'"));
+    if pids.is_empty() || !pids.len().is_multiple_of(3) || !pids.is_ascii() {
+        return Err(malformed());
+    }
+    let pids = (0..pids.len())
+        .step_by(3)
+        .map(|i| Pid::new(&pids[i..i + 3]).map_err(|_| malformed()))
+        .collect::<Result<Vec<Pid>, Error>>()?;
+    Ok((marker, pids))
+}
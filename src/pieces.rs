@@ -1,3 +1,4 @@
+use crate::error::Error;
 use crate::pid::Pid;
 use crate::board::Square;
 use crate::compass_groups::Direction;
@@ -6,15 +7,23 @@ use std::collections::HashMap;
 use std::sync::LazyLock as Lazy;
 use std::vec::Vec;
 use std::fmt;
-use std::str::FromStr;
 use strum::{IntoEnumIterator};
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Side {
     White,
     Black,
 }
 
+impl Side {
+    pub fn opposite(&self) -> Self {
+        match self {
+            Side::White => Side::Black,
+            Side::Black => Side::White,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum BasicPieceType {
     King,
@@ -39,7 +48,7 @@ impl BasicPieceType {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum PieceType {
     WhiteKing,
     BlackKing,
@@ -55,46 +64,26 @@ pub enum PieceType {
     BlackPawn,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum King_Locations {
-    WhiteKing(Square),
-    BlackKing(Square),
-}
-
-impl King_Locations {
-    pub fn new_white_king(square: Square) -> Self {
-        King_Locations::WhiteKing(square)
-    }
-    
-    pub fn new_black_king(square: Square) -> Self {
-        King_Locations::BlackKing(square)
-    }
-    
-    pub fn get_white_king_square(&self) -> Option<Square> {
-        match self {
-            King_Locations::WhiteKing(square) => Some(*square),
-            _ => None,
-        }
-    }
-    
-    pub fn get_black_king_square(&self) -> Option<Square> {
-        match self {
-            King_Locations::BlackKing(square) => Some(*square),
-            _ => None,
-        }
-    }
-    
-    pub fn update_white_king(&mut self, new_square: Square) {
-        *self = King_Locations::WhiteKing(new_square);
-    }
-    
-    pub fn update_black_king(&mut self, new_square: Square) {
-        *self = King_Locations::BlackKing(new_square);
+/// The Unicode figurine for a piece letter: outlined for White, filled for Black.
+/// Anything else is returned as it is.
+pub fn figurine(pchar: char) -> char {
+    match pchar {
+        'K' => '♔',
+        'Q' => '♕',
+        'R' => '♖',
+        'B' => '♗',
+        'N' => '♘',
+        'P' => '♙',
+        'k' => '♚',
+        'q' => '♛',
+        'r' => '♜',
+        'b' => '♝',
+        'n' => '♞',
+        'p' => '♟',
+        other => other,
     }
 }
 
-
-
 #[derive(Debug)]
 pub struct PieceTypeData {
     pub basic_piece_type: BasicPieceType,
@@ -195,6 +184,24 @@ impl PieceType {
         }
     }
 
+    /// The FEN letter, the inverse of `from_char`.
+    pub fn to_char(&self) -> char {
+        match self {
+            PieceType::WhiteKing => 'K',
+            PieceType::BlackKing => 'k',
+            PieceType::WhiteQueen => 'Q',
+            PieceType::BlackQueen => 'q',
+            PieceType::WhiteRook => 'R',
+            PieceType::BlackRook => 'r',
+            PieceType::WhiteBishop => 'B',
+            PieceType::BlackBishop => 'b',
+            PieceType::WhiteKnight => 'N',
+            PieceType::BlackKnight => 'n',
+            PieceType::WhitePawn => 'P',
+            PieceType::BlackPawn => 'p',
+        }
+    }
+
     pub fn get_data(&self) -> &'static PieceTypeData {
         match self {
             PieceType::WhiteKing => &WHITE_KING_DATA,
@@ -245,29 +252,21 @@ impl Piece {
             exchangers: HashMap::new(),
         }
     }
-    pub fn get_piece_data(&self) -> &'static PieceTypeData {
-        let piece_type_char = self.get_piece_type_as_char();
-        let piece_type_ref = PieceType::get_piece_type(piece_type_char);
-        if let Some(piece_type) = piece_type_ref {
-            piece_type.get_data()
-        } else {
-            panic!("Invalid piece type")
-        }
+    pub fn get_piece_data(&self) -> Result<&'static PieceTypeData, Error> {
+        self.pid.get_piece_data()
     }
 
     pub fn get_pid(&self) -> &Pid {
         &self.pid
     }
     pub fn get_square(&self) -> Square {
-        let sq = &self.pid.to_string()[0..2];
-        let square = Square::from_str(sq).unwrap();
-        square
+        self.pid.square()
     }
     pub fn get_piece_type_as_char(&self) -> char {
-        self.pid.to_string().chars().nth(2).unwrap()
+        self.pid.piece_char()
     }
     pub fn get_side(&self) -> Side {
-        match self.pid.to_string().chars().nth(2).unwrap().is_uppercase() {
+        match self.pid.piece_char().is_uppercase() {
             true => Side::White,
             _ => Side::Black,
         }
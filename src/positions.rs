@@ -0,0 +1,143 @@
+// src/positions.rs
+// A library of named test positions, each with a description and the motifs it
+// shows. New positions are added as entries of POSITIONS rather than as methods on
+// `Board`; tests, the shell (`load NAME`) and other tools look them up by name.
+use crate::board::Board;
+use crate::error::{Error, Result};
+use crate::pieces::Side;
+use strum::{Display, EnumIter, EnumString};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, EnumIter)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Motif {
+    Check,
+    DiscoveredCheck,
+    Pin,
+    Skewer,
+    Battery,
+    EnPassant,
+    Castling,
+    Promotion,
+    Opening,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Setup {
+    Fen(&'static str),
+    Pids(&'static [&'static str], Side),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NamedPosition {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub motifs: &'static [Motif],
+    pub setup: Setup,
+}
+
+impl NamedPosition {
+    /// The position with its exchangers built, checked for legality like any other
+    /// user supplied position.
+    pub fn board(&self) -> Result<Board> {
+        match self.setup {
+            Setup::Fen(fen) => Board::from_fen(fen),
+            Setup::Pids(pids, turn) => Board::from_pid_list(pids.to_vec(), turn),
+        }
+    }
+
+    pub fn has_motif(&self, motif: Motif) -> bool {
+        self.motifs.contains(&motif)
+    }
+}
+
+pub const POSITIONS: &[NamedPosition] = &[
+    NamedPosition {
+        name: "standard",
+        description: "the initial position",
+        motifs: &[Motif::Opening],
+        setup: Setup::Fen(crate::fen::STANDARD_FEN),
+    },
+    NamedPosition {
+        name: "double_discovered_check",
+        description: "Bf1+ b5 axb6: a bishop check blocked by a pawn that is then taken en passant, \
+                      uncovering the a1 rook",
+        motifs: &[Motif::Check, Motif::Pin, Motif::EnPassant, Motif::DiscoveredCheck],
+        setup: Setup::Pids(&["e1K", "a6k", "a5P", "b7p", "a1R", "g2B", "g1B", "d8N"], Side::White),
+    },
+    NamedPosition {
+        name: "custom_from",
+        description: "queen and rook batteries facing each other on the e-file",
+        motifs: &[Motif::Battery],
+        setup: Setup::Pids(
+            &["e1K", "e2Q", "e3R", "e4R", "b2B", "c6N", "e5P", "g4n", "g7b", "e6r", "e7r", "e8q", "f8k", "f6p"],
+            Side::White,
+        ),
+    },
+    NamedPosition {
+        name: "custom1",
+        description: "the white king in check from f3 with its b5 bishop pinned by the a5 rook",
+        motifs: &[Motif::Check, Motif::Pin],
+        setup: Setup::Pids(&["f7R", "e6p", "a5r", "b5B", "d5K", "d4P", "d3Q", "f3b", "c2n", "g2B", "a1k"], Side::White),
+    },
+    NamedPosition {
+        name: "pinned_knight",
+        description: "the e2 knight pinned to its king by the e7 rook, backed by the e8 queen",
+        motifs: &[Motif::Pin, Motif::Battery],
+        setup: Setup::Fen("k3q3/4r3/8/8/3N4/2P5/4N3/4K3 w - - 0 1"),
+    },
+    NamedPosition {
+        name: "castle_and_promote",
+        description: "an en passant capture on d6, castling on both wings and a pawn promoting on a8",
+        motifs: &[Motif::EnPassant, Motif::Castling, Motif::Promotion],
+        setup: Setup::Fen("r3k2r/1P6/8/3pP3/8/1N3N2/8/R3K2R w KQkq d6 0 1"),
+    },
+    NamedPosition {
+        name: "kiwipete",
+        description: "the perft test position with castling, pins, en passant and promotions in reach",
+        motifs: &[Motif::Castling, Motif::Pin, Motif::EnPassant, Motif::Promotion],
+        setup: Setup::Fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"),
+    },
+];
+
+pub fn find_position(name: &str) -> Result<&'static NamedPosition> {
+    POSITIONS.iter().find(|position| position.name == name).ok_or_else(|| Error::UnknownPosition(name.to_string()))
+}
+
+pub fn positions_with(motif: Motif) -> impl Iterator<Item = &'static NamedPosition> {
+    POSITIONS.iter().filter(move |position| position.has_motif(motif))
+}
+
+impl Board {
+    /// Shorthand for `find_position(name)?.board()`.
+    pub fn named(name: &str) -> Result<Board> {
+        find_position(name)?.board()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_every_position_loads() {
+        for position in POSITIONS {
+            let board = position.board().unwrap_or_else(|err| panic!("{}: {err}", position.name));
+            assert!(board.validate().is_ok(), "{}", position.name);
+            assert_eq!(POSITIONS.iter().filter(|p| p.name == position.name).count(), 1, "{} twice", position.name);
+        }
+        assert_eq!(Board::named("pinned_knight").unwrap().to_fen(), "k3q3/4r3/8/8/3N4/2P5/4N3/4K3 w - - 0 1");
+        assert_eq!(Board::named("nowhere").unwrap_err(), Error::UnknownPosition("nowhere".to_string()));
+    }
+
+    #[test]
+    fn test_positions_by_motif() {
+        assert_eq!(Motif::from_str("en-passant").unwrap(), Motif::EnPassant);
+        assert_eq!(Motif::DiscoveredCheck.to_string(), "discovered-check");
+        let names: Vec<&str> = positions_with(Motif::EnPassant).map(|p| p.name).collect();
+        assert_eq!(names, vec!["double_discovered_check", "castle_and_promote", "kiwipete"]);
+
+        let custom1 = Board::named("custom1").unwrap();
+        assert!(custom1.pin_lines().iter().any(|(_, _, chain)| *chain == "<b5Ba5r"));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::board::Square;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingPath {
+    /// `full_process_move`: every exchanger rebuilt from scratch.
+    Full,
+    /// `pre_processed_move`: only the exchangers touched by the move are updated.
+    /// Castling, which this path hands to the full rebuild, is recorded here too.
+    PreProcessed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveTiming {
+    pub from: Square,
+    pub to: Square,
+    pub path: ProcessingPath,
+    pub nanos: u128,
+    /// Exchanger chains the move changed, added or removed, counted alike on both paths.
+    pub exchanger_updates: usize,
+}
+
+/// Per-move timings and exchanger-update counts, carried from board to board along a
+/// line of play so they can be queried instead of printed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessingStats {
+    pub full_moves: u64,
+    pub full_nanos: u128,
+    pub full_exchanger_updates: u64,
+    pub pre_processed_moves: u64,
+    pub pre_processed_nanos: u128,
+    pub pre_processed_exchanger_updates: u64,
+    pub last_move: Option<MoveTiming>,
+}
+
+impl ProcessingStats {
+    pub fn record(&mut self, timing: MoveTiming) {
+        match timing.path {
+            ProcessingPath::Full => {
+                self.full_moves += 1;
+                self.full_nanos += timing.nanos;
+                self.full_exchanger_updates += timing.exchanger_updates as u64;
+            }
+            ProcessingPath::PreProcessed => {
+                self.pre_processed_moves += 1;
+                self.pre_processed_nanos += timing.nanos;
+                self.pre_processed_exchanger_updates += timing.exchanger_updates as u64;
+            }
+        }
+        self.last_move = Some(timing);
+    }
+
+    pub fn mean_full_nanos(&self) -> Option<u128> {
+        (self.full_moves > 0).then(|| self.full_nanos / self.full_moves as u128)
+    }
+
+    pub fn mean_pre_processed_nanos(&self) -> Option<u128> {
+        (self.pre_processed_moves > 0).then(|| self.pre_processed_nanos / self.pre_processed_moves as u128)
+    }
+}
@@ -0,0 +1,269 @@
+// src/repl.rs
+// The command shell behind the `neetroc_bot` binary. A session keeps every board
+// reached so far so `undo` can step back; each command returns the text to print.
+use crate::board::{Board, Square};
+use crate::diagram::DiagramOptions;
+use crate::error::{Error, Result};
+use crate::eval::evaluate;
+use crate::fen::STANDARD_FEN;
+use crate::pieces::Side;
+use crate::positions::{positions_with, Motif, NamedPosition, POSITIONS};
+use crate::search::{search, SearchLimits};
+use crate::svg::SvgOptions;
+use std::fmt::Write;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const HELP: &str = "\
+commands:
+  fen [FEN]             load a position, or print the current FEN
+  pids PID... [w|b]     load a position from pids, e.g. pids e1K e8k a2P w
+  load NAME             load a position from the named position library
+  positions [MOTIF]     list the named positions, or those showing a motif
+  move MOVE             play a move in UCI (e2e4) or SAN (e4)
+  undo                  take back the last move
+  show [SQUARE]         draw the board with its pins, and a square's attackers
+                        and defenders when one is given
+  dump                  print every piece with its exchangers
+  svg FILE              write the board and its exchanger arrows as SVG
+  dot                   print the exchanger graph in Graphviz DOT
+  moves                 list the legal moves
+  exchangers SQUARE     print the exchangers of the piece on a square
+  pins                  print the pin lines held by the kings
+  checks                print the checking pieces
+  eval                  static evaluation for the side to move
+  go [depth N|time MS]  search the position (default depth 4)
+  perft N               count leaf positions N plies ahead, split by move
+  help                  this text
+  quit";
+
+pub enum Reply {
+    Text(String),
+    Quit,
+}
+
+pub struct Session {
+    /// Every board since the last load; the last one is current.
+    history: Vec<Board>,
+}
+
+fn invalid(command: &str, reason: impl Into<String>) -> Error {
+    Error::InvalidCommand { command: command.to_string(), reason: reason.into() }
+}
+
+fn parse_square(command: &str, square: &str) -> Result<Square> {
+    Square::from_str(square).map_err(|_| invalid(command, format!("'{square}' is not a square")))
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
+
+impl Session {
+    /// Starts from the standard position.
+    pub fn new() -> Self {
+        Session { history: vec![Board::from_fen(STANDARD_FEN).expect("standard position")] }
+    }
+
+    pub fn board(&self) -> &Board {
+        self.history.last().expect("history is never empty")
+    }
+
+    fn load(&mut self, board: Board) {
+        self.history = vec![board];
+    }
+
+    pub fn execute(&mut self, line: &str) -> Result<Reply> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = words.split_first() else { return Ok(Reply::Text(String::new())) };
+        let text = match *command {
+            "quit" | "exit" => return Ok(Reply::Quit),
+            "help" => HELP.to_string(),
+            "fen" if args.is_empty() => self.board().to_fen(),
+            "fen" => {
+                self.load(Board::from_fen(&args.join(" "))?);
+                self.board().to_fen()
+            }
+            "pids" => self.pids(args)?,
+            "load" => {
+                let [name] = args else { return Err(invalid(line, "expected a position name")) };
+                self.load(Board::named(name)?);
+                self.board().to_fen()
+            }
+            "positions" => {
+                let listed: Vec<&NamedPosition> = match args {
+                    [] => POSITIONS.iter().collect(),
+                    [motif] => {
+                        let motif = Motif::from_str(motif).map_err(|_| invalid(line, format!("'{motif}' is not a motif")))?;
+                        positions_with(motif).collect()
+                    }
+                    _ => return Err(invalid(line, "expected at most one motif")),
+                };
+                let lines: Vec<String> = listed.iter().map(|p| format!("{:<24}{}", p.name, p.description)).collect();
+                lines.join("\n")
+            }
+            "move" => {
+                let [notation] = args else { return Err(invalid(line, "expected one move")) };
+                let board = self.board();
+                let mv = board.parse_uci(notation).or_else(|_| board.parse_san(notation))?;
+                let san = board.to_san(mv)?;
+                let next = board.make_move(mv)?;
+                self.history.push(next);
+                format!("{san} ({mv})")
+            }
+            "undo" => {
+                if self.history.len() == 1 {
+                    return Err(invalid(line, "no move to take back"));
+                }
+                self.history.pop();
+                self.board().to_fen()
+            }
+            "show" => {
+                let mut options = DiagramOptions::unicode().with_colour().with_pins();
+                if let [square] = args {
+                    options = options.with_focus(parse_square(line, square)?);
+                }
+                format!("{}\n{}", self.board().diagram(&options), self.board().to_fen())
+            }
+            "dump" => self.board().to_ordered_string().trim_end().to_string(),
+            "dot" => self.board().to_dot().trim_end().to_string(),
+            "svg" => {
+                let [path] = args else { return Err(invalid(line, "expected a file name")) };
+                self.board()
+                    .write_svg(path, &SvgOptions::default())
+                    .map_err(|err| invalid(line, format!("cannot write {path}: {err}")))?;
+                format!("wrote {path}")
+            }
+            "moves" => {
+                let board = self.board();
+                let sans: Vec<String> = board.legal_moves().into_iter().map(|mv| board.to_san(mv)).collect::<Result<_>>()?;
+                sans.join(" ")
+            }
+            "exchangers" => {
+                let [square] = args else { return Err(invalid(line, "expected a square")) };
+                let square = parse_square(line, square)?;
+                self.board().get_piece_on(square).ok_or(Error::EmptySquare(square))?.to_string()
+            }
+            "pins" => {
+                let lines = self.board().pin_lines();
+                if lines.is_empty() {
+                    "no pins".to_string()
+                } else {
+                    let found: Vec<String> = lines.iter().map(|(king, drctn, chain)| format!("{king} {drctn:?}: {chain}")).collect();
+                    found.join("\n")
+                }
+            }
+            "checks" => {
+                let board = self.board();
+                match board.check_kind() {
+                    Some(kind) => {
+                        let checkers: Vec<String> = board.checks().iter().map(|pid| pid.to_string()).collect();
+                        format!("{kind:?}: {}", checkers.join(" "))
+                    }
+                    None => "no check".to_string(),
+                }
+            }
+            "eval" => format!("{} cp for {:?}", evaluate(self.board()), self.board().turn()),
+            "go" => {
+                let limits = match args {
+                    [] => SearchLimits::depth(4),
+                    ["depth", n] => SearchLimits::depth(n.parse().map_err(|_| invalid(line, "depth must be a number"))?),
+                    ["time", ms] => SearchLimits::time(Duration::from_millis(
+                        ms.parse().map_err(|_| invalid(line, "time must be milliseconds"))?,
+                    )),
+                    _ => return Err(invalid(line, "expected 'go depth N' or 'go time MS'")),
+                };
+                let result = search(self.board(), limits);
+                let best = match result.best_move {
+                    Some(mv) => self.board().to_san(mv)?,
+                    None => "none".to_string(),
+                };
+                format!(
+                    "bestmove {best} score {} depth {} nodes {} time {}ms",
+                    result.score,
+                    result.depth,
+                    result.nodes,
+                    result.elapsed.as_millis()
+                )
+            }
+            "perft" => {
+                let [depth] = args else { return Err(invalid(line, "expected a depth")) };
+                let depth: u32 = depth.parse().map_err(|_| invalid(line, "depth must be a number"))?;
+                let mut out = String::new();
+                // depth 0 plays no move but still counts the position itself
+                let mut total = if depth == 0 { 1 } else { 0 };
+                for (mv, count) in self.board().perft_divide(depth) {
+                    writeln!(out, "{mv}: {count}").unwrap();
+                    total += count;
+                }
+                write!(out, "total: {total}").unwrap();
+                out
+            }
+            _ => return Err(invalid(line, "unknown command, try 'help'")),
+        };
+        Ok(Reply::Text(text))
+    }
+
+    fn pids(&mut self, args: &[&str]) -> Result<String> {
+        let (turn, pids) = match args.split_last() {
+            Some((&"w", rest)) => (Side::White, rest),
+            Some((&"b", rest)) => (Side::Black, rest),
+            _ => (Side::White, args),
+        };
+        self.load(Board::from_pid_list(pids.to_vec(), turn)?);
+        Ok(self.board().to_fen())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(session: &mut Session, line: &str) -> String {
+        match session.execute(line).unwrap() {
+            Reply::Text(text) => text,
+            Reply::Quit => panic!("unexpected quit"),
+        }
+    }
+
+    #[test]
+    fn test_session_moves_and_undo() {
+        let mut session = Session::new();
+        assert_eq!(run(&mut session, "move e2e4"), "e4 (e2e4)");
+        assert_eq!(run(&mut session, "move c5"), "c5 (c7c5)");
+        assert_eq!(run(&mut session, "undo"), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert!(session.execute("move e2e4").is_err());
+        run(&mut session, "undo");
+        assert!(matches!(session.execute("undo"), Err(Error::InvalidCommand { .. })));
+        assert!(run(&mut session, "perft 2").ends_with("total: 400"));
+        assert_eq!(run(&mut session, "perft 0"), "total: 1");
+        assert_eq!(run(&mut session, "eval"), "0 cp for White");
+        assert!(matches!(session.execute("quit"), Ok(Reply::Quit)));
+        assert!(session.execute("fly").is_err());
+    }
+
+    #[test]
+    fn test_session_inspects_positions() {
+        let mut session = Session::new();
+        run(&mut session, "pids e1K e8k e2N e7r b");
+        assert_eq!(run(&mut session, "fen"), "4k3/4r3/8/8/8/8/4N3/4K3 b - - 0 1");
+        assert_eq!(run(&mut session, "pins"), "e1 N: <e2Ne7r");
+        assert!(run(&mut session, "exchangers e2").starts_with("e2N |"));
+        assert!(matches!(session.execute("exchangers e4"), Err(Error::EmptySquare(_))));
+        assert_eq!(run(&mut session, "checks"), "no check");
+        assert!(run(&mut session, "show e2").ends_with("4k3/4r3/8/8/8/8/4N3/4K3 b - - 0 1"));
+        assert!(session.execute("show e9").is_err());
+
+        run(&mut session, "fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        assert!(run(&mut session, "go depth 2").starts_with("bestmove Ra8#"));
+        run(&mut session, "move Ra8");
+        assert_eq!(run(&mut session, "checks"), "Direct: a8R");
+
+        assert_eq!(run(&mut session, "load pinned_knight"), "k3q3/4r3/8/8/3N4/2P5/4N3/4K3 w - - 0 1");
+        assert!(matches!(session.execute("load nowhere"), Err(Error::UnknownPosition(_))));
+        assert!(run(&mut session, "positions pin").starts_with("double_discovered_check "));
+        assert!(session.execute("positions fork").is_err());
+    }
+}
@@ -0,0 +1,263 @@
+// src/scenario.rs
+// Plain-text scenarios for comparing the two move paths. A scenario names a
+// position, a move list and, optionally, the exchangers expected after given plies:
+//
+//   # the canonical two-path scenario
+//   name: bishop check and en passant
+//   position: double_discovered_check
+//   moves: Bf1+ b5 axb6
+//   expect 1: a6k SE f1B
+//   expect 3: a6k S a1R
+//
+// `position:` names an entry of the position library; `fen:` or `pids:` with
+// `turn:` set up any other position. An expectation gives a ply (1 is the first
+// move), a pid, a direction and the chain, or '-' for no exchanger.
+use crate::board::Board;
+use crate::compass_groups::Direction;
+use crate::error::{Error, Result};
+use crate::pid::Pid;
+use crate::pieces::Side;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expectation {
+    pub ply: usize,
+    pub pid: Pid,
+    pub direction: Direction,
+    pub chain: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub name: String,
+    pub start: Board,
+    pub moves: Vec<String>,
+    pub expectations: Vec<Expectation>,
+}
+
+enum Position {
+    Named(String),
+    Fen(String),
+    Pids(Vec<String>),
+}
+
+fn invalid(line: usize, reason: impl Into<String>) -> Error {
+    Error::InvalidScenario { line, reason: reason.into() }
+}
+
+/// Reads a scenario; `default_name` is used when the text has no `name:` line.
+pub fn parse_scenario(text: &str, default_name: &str) -> Result<Scenario> {
+    let mut name = default_name.to_string();
+    let mut position = None;
+    let mut turn = Side::White;
+    let mut moves = Vec::new();
+    let mut expectations = Vec::new();
+
+    for (index, raw) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (key, value) = line.split_once(':').ok_or_else(|| invalid(number, "expected 'key: value'"))?;
+        let value = value.trim();
+        match key.trim() {
+            "name" => name = value.to_string(),
+            "position" => position = Some(Position::Named(value.to_string())),
+            "fen" => position = Some(Position::Fen(value.to_string())),
+            "pids" => position = Some(Position::Pids(value.split_whitespace().map(str::to_string).collect())),
+            "turn" => {
+                turn = match value {
+                    "w" => Side::White,
+                    "b" => Side::Black,
+                    _ => return Err(invalid(number, format!("turn must be 'w' or 'b', not '{value}'"))),
+                }
+            }
+            "moves" => moves.extend(value.split_whitespace().map(str::to_string)),
+            key if key.starts_with("expect") => {
+                let ply = key["expect".len()..]
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|ply| *ply > 0)
+                    .ok_or_else(|| invalid(number, "expected 'expect <ply>:' with a ply from 1"))?;
+                let [pid, direction, chain] = value.split_whitespace().collect::<Vec<_>>()[..] else {
+                    return Err(invalid(number, "expected '<pid> <direction> <chain or ->'"));
+                };
+                expectations.push(Expectation {
+                    ply,
+                    pid: Pid::new(pid).map_err(|err| invalid(number, err.to_string()))?,
+                    direction: Direction::from_str(direction)
+                        .map_err(|_| invalid(number, format!("'{direction}' is not a direction")))?,
+                    chain: (chain != "-").then(|| chain.to_string()),
+                });
+            }
+            other => return Err(invalid(number, format!("unknown key '{other}'"))),
+        }
+    }
+
+    let start = match position.ok_or_else(|| invalid(0, "no 'position:', 'fen:' or 'pids:' line"))? {
+        Position::Named(name) => Board::named(&name)?,
+        Position::Fen(fen) => Board::from_fen(&fen)?,
+        Position::Pids(pids) => Board::from_pid_list(pids.iter().map(String::as_str).collect(), turn)?,
+    };
+    if let Some(late) = expectations.iter().find(|e| e.ply > moves.len()) {
+        return Err(invalid(0, format!("expectation for ply {} but only {} moves", late.ply, moves.len())));
+    }
+    Ok(Scenario { name, start, moves, expectations })
+}
+
+#[derive(Debug, Clone)]
+pub struct ScenarioOutcome {
+    pub name: String,
+    /// Empty when the scenario passed.
+    pub failures: Vec<String>,
+    /// Plies only the full path could play, promotions and castling; they do not
+    /// fail the scenario.
+    pub skipped: Vec<String>,
+}
+
+impl ScenarioOutcome {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for ScenarioOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", if self.passed() { "PASS" } else { "FAIL" }, self.name)?;
+        for failure in &self.failures {
+            write!(f, "\n    {failure}")?;
+        }
+        for skipped in &self.skipped {
+            write!(f, "\n    skipped {skipped}")?;
+        }
+        Ok(())
+    }
+}
+
+fn check_expectations(failures: &mut Vec<String>, scenario: &Scenario, ply: usize, path: &str, board: &Board) {
+    for expectation in scenario.expectations.iter().filter(|e| e.ply == ply) {
+        let found = board
+            .get_piece_on(expectation.pid.square())
+            .filter(|piece| piece.get_pid() == &expectation.pid)
+            .map(|piece| piece.exchangers.get(&expectation.direction).cloned());
+        let Some(found) = found else {
+            failures.push(format!("ply {ply} {path}: {} is not on the board", expectation.pid));
+            continue;
+        };
+        if found != expectation.chain {
+            let show = |chain: &Option<String>| chain.clone().unwrap_or_else(|| "-".to_string());
+            failures.push(format!(
+                "ply {ply} {path}: {} {} expected {} got {}",
+                expectation.pid,
+                expectation.direction,
+                show(&expectation.chain),
+                show(&found)
+            ));
+        }
+    }
+}
+
+/// Plays the moves through both paths. Every ply the pre-processed board must match
+/// the full rebuild and both must meet that ply's expectations. Play continues from
+/// the full rebuild, so one divergent update does not spoil the plies after it.
+pub fn run_scenario(scenario: &Scenario) -> ScenarioOutcome {
+    let mut failures = Vec::new();
+    let mut skipped = Vec::new();
+    let mut board = scenario.start.clone();
+    for (index, san) in scenario.moves.iter().enumerate() {
+        let ply = index + 1;
+        let both = board
+            .parse_san(san)
+            .and_then(|mv| Ok((board.play_both_paths(mv)?, board.pre_processed_unsupported(mv))));
+        let ((full, incremental), unsupported) = match both {
+            Ok(both) => both,
+            Err(err) => {
+                failures.push(format!("ply {ply} {san}: {err}"));
+                break;
+            }
+        };
+        check_expectations(&mut failures, scenario, ply, "full", &full);
+        match incremental {
+            Some(incremental) => {
+                for difference in full.diff(&incremental) {
+                    failures.push(format!("ply {ply} {san}: {difference}"));
+                }
+                check_expectations(&mut failures, scenario, ply, "pre-processed", &incremental);
+            }
+            None => skipped.push(format!("ply {ply} {san}: no pre-processed path for {}", unsupported.unwrap_or("this move"))),
+        }
+        board = full;
+    }
+    ScenarioOutcome { name: scenario.name.clone(), failures, skipped }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANONICAL: &str = "
+# the canonical two-path scenario
+name: bishop check and en passant
+position: double_discovered_check
+moves: Bf1+ b5 axb6
+expect 1: a6k SE f1B
+expect 2: a6k SE <b5pf1B
+expect 3: a6k S a1R
+";
+
+    #[test]
+    fn test_parse_scenario() {
+        let scenario = parse_scenario(CANONICAL, "unnamed").unwrap();
+        assert_eq!(scenario.name, "bishop check and en passant");
+        assert_eq!(scenario.moves, vec!["Bf1+", "b5", "axb6"]);
+        assert_eq!(scenario.expectations[1].chain.as_deref(), Some("<b5pf1B"));
+        assert_eq!(parse_scenario("fen: 4k3/8/8/8/8/8/8/4K3 w - - 0 1", "bare").unwrap().name, "bare");
+        let pids = parse_scenario("pids: e1K e8k e2N\nturn: b", "pids").unwrap();
+        assert_eq!(pids.start.to_fen(), "4k3/8/8/8/8/8/4N3/4K3 b - - 0 1");
+        assert_eq!(
+            parse_scenario("position: nowhere", "x").unwrap_err(),
+            Error::UnknownPosition("nowhere".to_string())
+        );
+
+        let error = |text: &str| match parse_scenario(text, "x") {
+            Err(Error::InvalidScenario { line, .. }) => line,
+            other => panic!("expected an invalid scenario, got {other:?}"),
+        };
+        assert_eq!(error("moves: e4"), 0);
+        assert_eq!(error("fen: 4k3/8/8/8/8/8/8/4K3 w - - 0 1\nturn: x"), 2);
+        assert_eq!(error("fen: 4k3/8/8/8/8/8/8/4K3 w - - 0 1\nexpect 1: e1K Q -"), 2);
+        assert_eq!(error("fen: 4k3/8/8/8/8/8/8/4K3 w - - 0 1\nexpect 1: e1K N -"), 0);
+    }
+
+    #[test]
+    fn test_run_scenario_checks_both_paths() {
+        let scenario = parse_scenario(CANONICAL, "unnamed").unwrap();
+        let outcome = run_scenario(&scenario);
+        assert!(outcome.passed(), "{outcome}");
+        assert_eq!(outcome.to_string(), "PASS bishop check and en passant");
+
+        let wrong = parse_scenario(&format!("{CANONICAL}expect 3: a6k S -\nmoves: Kd1\n"), "unnamed").unwrap();
+        let outcome = run_scenario(&wrong);
+        assert_eq!(
+            outcome.failures,
+            vec![
+                "ply 3 full: a6k S expected - got a1R".to_string(),
+                "ply 3 pre-processed: a6k S expected - got a1R".to_string(),
+                "ply 4 Kd1: cannot read move 'Kd1': no legal move matches".to_string(),
+            ]
+        );
+
+        let promotion = parse_scenario("fen: 7k/P7/8/8/8/8/8/K7 w - - 0 1\nmoves: a8=Q+", "promotion").unwrap();
+        let outcome = run_scenario(&promotion);
+        assert!(outcome.passed(), "{outcome}");
+        assert_eq!(outcome.to_string(), "PASS promotion\n    skipped ply 1 a8=Q+: no pre-processed path for promotions");
+
+        let castling = parse_scenario("fen: 4k3/8/8/8/8/8/8/4K2R w K - 0 1\nmoves: O-O Kd7", "castling").unwrap();
+        let outcome = run_scenario(&castling);
+        assert!(outcome.passed(), "{outcome}");
+        assert_eq!(outcome.skipped, vec!["ply 1 O-O: no pre-processed path for castling".to_string()]);
+    }
+}
@@ -0,0 +1,182 @@
+// src/search.rs
+// Iterative deepening alpha-beta over `Board::legal_moves`. Moves are played with
+// `play_unprocessed` on an exchanger-free copy, so the search never pays for
+// exchanger upkeep.
+use crate::board::Board;
+use crate::eval::{evaluate, piece_value, MATE};
+use crate::moves::Move;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub time: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u32) -> Self {
+        SearchLimits { depth: Some(depth), time: None }
+    }
+
+    pub fn time(time: Duration) -> Self {
+        SearchLimits { depth: None, time: Some(time) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    /// Centipawns for the side to move; mates are scored from `eval::MATE`.
+    pub score: i32,
+    /// The deepest iteration that completed.
+    pub depth: u32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+/// Without a depth limit the search deepens until the time runs out, or to this depth.
+const MAX_DEPTH: u32 = 64;
+
+struct Searcher {
+    deadline: Option<Instant>,
+    nodes: u64,
+    aborted: bool,
+}
+
+impl Searcher {
+    fn out_of_time(&mut self) -> bool {
+        if !self.aborted && self.nodes.is_multiple_of(256) && self.deadline.is_some_and(|d| Instant::now() >= d) {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    /// Captures first, most valuable victim by least valuable attacker, then promotions.
+    fn ordered_moves(board: &Board, first: Option<Move>) -> Vec<Move> {
+        let mut moves = board.legal_moves();
+        let weight = |mv: &Move| -> i32 {
+            if Some(*mv) == first {
+                return i32::MAX;
+            }
+            let victim = board.get_piece_on(mv.to).map_or(0, |p| piece_value(p.get_piece_type_as_char()));
+            let attacker = board.get_piece_on(mv.from).map_or(0, |p| piece_value(p.get_piece_type_as_char()));
+            let capture = if board.is_capture(mv) { 10 * victim.max(100) - attacker / 10 } else { 0 };
+            capture + mv.promotion.map_or(0, piece_value)
+        };
+        moves.sort_by_key(|mv| std::cmp::Reverse(weight(mv)));
+        moves
+    }
+
+    fn negamax(&mut self, board: &Board, depth: u32, ply: u32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.out_of_time() {
+            return 0;
+        }
+        if depth == 0 {
+            return self.quiesce(board, alpha, beta);
+        }
+        let moves = Searcher::ordered_moves(board, None);
+        if moves.is_empty() {
+            return if board.in_check() { -(MATE - ply as i32) } else { 0 };
+        }
+        for mv in moves {
+            let Ok(child) = board.play_unprocessed(mv) else { continue };
+            let score = -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha);
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    /// Settles the position by playing out captures before trusting the static eval.
+    fn quiesce(&mut self, board: &Board, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        let stand_pat = evaluate(board);
+        if stand_pat >= beta {
+            return beta;
+        }
+        alpha = alpha.max(stand_pat);
+        for mv in Searcher::ordered_moves(board, None).into_iter().filter(|mv| board.is_capture(mv)) {
+            if self.out_of_time() {
+                break;
+            }
+            let Ok(child) = board.play_unprocessed(mv) else { continue };
+            let score = -self.quiesce(&child, -beta, -alpha);
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+/// Searches the side to move's best move within the limits. With neither limit set
+/// the search stops at depth 4.
+pub fn search(board: &Board, limits: SearchLimits) -> SearchResult {
+    let start = Instant::now();
+    let root = board.without_exchangers();
+    let max_depth = match limits {
+        SearchLimits { depth: Some(depth), .. } => depth,
+        SearchLimits { depth: None, time: Some(_) } => MAX_DEPTH,
+        SearchLimits { depth: None, time: None } => 4,
+    };
+    let mut searcher = Searcher { deadline: limits.time.map(|t| start + t), nodes: 0, aborted: false };
+    let mut result = SearchResult { best_move: None, score: 0, depth: 0, nodes: 0, elapsed: Duration::ZERO };
+
+    for depth in 1..=max_depth {
+        let mut alpha = -MATE - 1;
+        let mut best = None;
+        for mv in Searcher::ordered_moves(&root, result.best_move) {
+            let Ok(child) = root.play_unprocessed(mv) else { continue };
+            let score = -searcher.negamax(&child, depth - 1, 1, -MATE - 1, -alpha);
+            if searcher.aborted {
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                best = Some(mv);
+            }
+        }
+        if searcher.aborted {
+            break;
+        }
+        result.best_move = best;
+        result.score = alpha;
+        result.depth = depth;
+        // a forced mate cannot be improved on by looking deeper
+        if best.is_none() || alpha.abs() >= MATE - MAX_DEPTH as i32 {
+            break;
+        }
+    }
+    if result.best_move.is_none() {
+        // out of time before depth 1 finished: any legal move beats none
+        result.best_move = Searcher::ordered_moves(&root, None).first().copied();
+    }
+    result.nodes = searcher.nodes;
+    result.elapsed = start.elapsed();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square::*;
+
+    #[test]
+    fn test_search_finds_mate_and_material() {
+        let back_rank = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let found = search(&back_rank, SearchLimits::depth(2));
+        assert_eq!(found.best_move, Some(Move::new(a1, a8)));
+        assert_eq!(found.score, MATE - 1);
+
+        let hanging_queen = Board::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+        let found = search(&hanging_queen, SearchLimits::depth(1));
+        assert_eq!(found.best_move, Some(Move::new(d1, d5)));
+
+        let timed = search(&hanging_queen, SearchLimits::time(Duration::from_millis(50)));
+        assert!(timed.depth >= 1 && timed.best_move.is_some());
+    }
+}
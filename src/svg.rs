@@ -0,0 +1,242 @@
+// src/svg.rs
+// Standalone SVG pictures of a board with an arrow for every exchanger relation,
+// for bug reports and documentation. Arrow colours tell direct attacks, x-ray
+// continuations, pins ('<' chains) and skewers ('>' chains) apart.
+use crate::board::{Board, Square};
+use crate::compass_groups::Direction;
+use crate::pid::split_chain;
+use crate::pieces::figurine;
+use std::fmt::Write;
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrowKind {
+    /// The first piece of a chain, bearing on the square directly.
+    Direct,
+    /// A piece further down a chain, bearing through the one in front of it.
+    XRay,
+    Pin,
+    Skewer,
+    /// An exchanger chain that cannot be read, drawn as a '?' on the square holding it.
+    Malformed,
+}
+
+impl ArrowKind {
+    fn colour(&self) -> &'static str {
+        match self {
+            ArrowKind::Direct => "#d62728",
+            ArrowKind::XRay => "#ff7f0e",
+            ArrowKind::Pin => "#1f77b4",
+            ArrowKind::Skewer => "#9467bd",
+            ArrowKind::Malformed => "#e00000",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ArrowKind::Direct => "direct",
+            ArrowKind::XRay => "xray",
+            ArrowKind::Pin => "pin",
+            ArrowKind::Skewer => "skewer",
+            ArrowKind::Malformed => "malformed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExchangerArrow {
+    pub from: Square,
+    pub to: Square,
+    pub kind: ArrowKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvgOptions {
+    /// Side of one square in pixels.
+    pub square_size: u32,
+    pub arrows: bool,
+    pub coordinates: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions { square_size: 45, arrows: true, coordinates: true }
+    }
+}
+
+const LIGHT: &str = "#f0d9b5";
+const DARK: &str = "#b58863";
+
+impl Board {
+    /// One arrow per link of every exchanger chain, ordered by target square then
+    /// direction. Pin and skewer arrows run from the slider to the king; a chain that
+    /// cannot be read gives a `Malformed` arrow from the square to itself.
+    pub fn exchanger_arrows(&self) -> Vec<ExchangerArrow> {
+        let mut targets: Vec<Square> = self.iter_pieces().map(|(square, _)| *square).collect();
+        targets.sort_by_key(|square| square.index());
+        let mut arrows = Vec::new();
+        for target in targets {
+            let Some(piece) = self.get_piece_on(target) else { continue };
+            for drctn in Direction::iter() {
+                let Some(chain) = piece.exchangers.get(&drctn) else { continue };
+                let Ok((marker, pids)) = split_chain(chain) else {
+                    arrows.push(ExchangerArrow { from: target, to: target, kind: ArrowKind::Malformed });
+                    continue;
+                };
+                let squares: Vec<Square> = pids.iter().map(|pid| pid.square()).collect();
+                match marker {
+                    Some(marker) => {
+                        // the piece in between comes first, the slider behind it second
+                        let kind = if marker == '<' { ArrowKind::Pin } else { ArrowKind::Skewer };
+                        if let Some(slider) = squares.get(1) {
+                            arrows.push(ExchangerArrow { from: *slider, to: target, kind });
+                        }
+                    }
+                    None => {
+                        let mut ahead = target;
+                        for (i, square) in squares.iter().enumerate() {
+                            let kind = if i == 0 { ArrowKind::Direct } else { ArrowKind::XRay };
+                            arrows.push(ExchangerArrow { from: *square, to: ahead, kind });
+                            ahead = *square;
+                        }
+                    }
+                }
+            }
+        }
+        arrows
+    }
+
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let size = options.square_size as f64;
+        let margin = if options.coordinates { size / 2.0 } else { 0.0 };
+        let width = 8.0 * size + 2.0 * margin;
+        // centre of a square, White at the bottom
+        let centre = |square: Square| {
+            (
+                margin + (square.file().index() as f64 + 0.5) * size,
+                margin + (7.5 - square.rank().index() as f64) * size,
+            )
+        };
+
+        let mut svg = String::new();
+        writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{width}" viewBox="0 0 {width} {width}">"#).unwrap();
+        svg.push_str("<defs>\n");
+        for kind in [ArrowKind::Direct, ArrowKind::XRay, ArrowKind::Pin, ArrowKind::Skewer] {
+            writeln!(
+                svg,
+                r#"<marker id="head-{}" markerWidth="4" markerHeight="4" refX="2" refY="2" orient="auto"><path d="M0,0 L4,2 L0,4 z" fill="{}"/></marker>"#,
+                kind.name(),
+                kind.colour()
+            )
+            .unwrap();
+        }
+        svg.push_str("</defs>\n");
+        writeln!(svg, r##"<rect width="{width}" height="{width}" fill="#ffffff"/>"##).unwrap();
+
+        for rank in 0..8 {
+            for file in 0..8 {
+                let fill = if (file + rank) % 2 == 1 { LIGHT } else { DARK };
+                let (x, y) = (margin + file as f64 * size, margin + (7 - rank) as f64 * size);
+                writeln!(svg, r#"<rect x="{x}" y="{y}" width="{size}" height="{size}" fill="{fill}"/>"#).unwrap();
+            }
+        }
+        if options.coordinates {
+            for i in 0..8 {
+                let along = margin + (i as f64 + 0.5) * size;
+                let file = (b'a' + i as u8) as char;
+                let rank = 8 - i;
+                writeln!(svg, r#"<text x="{along}" y="{}" font-size="{}" text-anchor="middle">{file}</text>"#, width - margin / 3.0, margin / 2.0).unwrap();
+                writeln!(svg, r#"<text x="{}" y="{along}" font-size="{}" text-anchor="middle" dominant-baseline="central">{rank}</text>"#, margin / 2.0, margin / 2.0).unwrap();
+            }
+        }
+
+        let mut pieces: Vec<(Square, char)> = self.iter_pieces().map(|(square, piece)| (*square, piece.get_piece_type_as_char())).collect();
+        pieces.sort_by_key(|(square, _)| square.index());
+        for (square, pchar) in pieces {
+            let (x, y) = centre(square);
+            let (fill, stroke) = if pchar.is_uppercase() { ("#ffffff", "#000000") } else { ("#000000", "#ffffff") };
+            writeln!(
+                svg,
+                r#"<text x="{x}" y="{y}" font-size="{}" text-anchor="middle" dominant-baseline="central" fill="{fill}" stroke="{stroke}" stroke-width="0.8">{}</text>"#,
+                size * 0.8,
+                // the filled figurines, coloured by the fill, read best at small sizes
+                figurine(pchar.to_ascii_lowercase())
+            )
+            .unwrap();
+        }
+
+        if options.arrows {
+            for arrow in self.exchanger_arrows() {
+                let (x1, y1) = centre(arrow.from);
+                if arrow.kind == ArrowKind::Malformed {
+                    writeln!(
+                        svg,
+                        r#"<text class="malformed" x="{:.1}" y="{:.1}" font-size="{:.1}" fill="{}">?</text>"#,
+                        x1 + size * 0.15,
+                        y1 - size * 0.15,
+                        size * 0.4,
+                        arrow.kind.colour()
+                    )
+                    .unwrap();
+                    continue;
+                }
+                let (x2, y2) = centre(arrow.to);
+                // stop short of the centre so the head does not cover the piece
+                let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+                let shorten = size * 0.3 / length;
+                let (x2, y2) = (x2 - (x2 - x1) * shorten, y2 - (y2 - y1) * shorten);
+                let dash = if arrow.kind == ArrowKind::XRay { r#" stroke-dasharray="6,4""# } else { "" };
+                writeln!(
+                    svg,
+                    r#"<line class="{}" x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" stroke="{}" stroke-width="{:.1}" stroke-opacity="0.8"{dash} marker-end="url(#head-{})"/>"#,
+                    arrow.kind.name(),
+                    arrow.kind.colour(),
+                    size / 12.0,
+                    arrow.kind.name()
+                )
+                .unwrap();
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn write_svg(&self, path: impl AsRef<Path>, options: &SvgOptions) -> std::io::Result<()> {
+        std::fs::write(path, self.to_svg(options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square::*;
+
+    #[test]
+    fn test_exchanger_arrows_by_kind() {
+        // the e7 rook pins the e2 knight, backed up by the e8 queen; the c3 pawn
+        // defends the d4 knight
+        let board = Board::named("pinned_knight").unwrap();
+        let arrows = board.exchanger_arrows();
+        let has = |from, to, kind| arrows.contains(&ExchangerArrow { from, to, kind });
+        assert!(has(c3, d4, ArrowKind::Direct));
+        assert!(has(e7, e2, ArrowKind::Direct));
+        assert!(has(e8, e7, ArrowKind::XRay));
+        assert!(has(e7, e1, ArrowKind::Pin));
+        assert!(!arrows.iter().any(|a| a.kind == ArrowKind::Skewer));
+    }
+
+    #[test]
+    fn test_svg_is_standalone() {
+        let board = Board::named("pinned_knight").unwrap();
+        let svg = board.to_svg(&SvgOptions::default());
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="405" height="405""#));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<line ").count(), board.exchanger_arrows().len());
+        assert_eq!(svg.matches("♞").count(), 2);
+        assert!(svg.contains(r#"class="pin""#));
+
+        let bare = board.to_svg(&SvgOptions { arrows: false, coordinates: false, ..SvgOptions::default() });
+        assert!(!bare.contains("<line ") && bare.contains(r#"width="360""#));
+    }
+}
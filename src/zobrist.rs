@@ -0,0 +1,86 @@
+// src/zobrist.rs
+// Zobrist position keys: one random number per (piece, square), side to move,
+// castling right and en passant file, xor-ed together. Equal positions get equal
+// keys, so they serve for repetition checks and as node keys in a game tree.
+use crate::board::Board;
+use crate::pieces::Side;
+
+const PIECE_CHARS: &str = "PNBRQKpnbrqk";
+
+// splitmix64, evaluated at compile time to fill the tables
+const fn splitmix(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+const fn build_keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut keys = [0u64; N];
+    let mut i = 0;
+    while i < N {
+        keys[i] = splitmix(seed.wrapping_add((i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)));
+        i += 1;
+    }
+    keys
+}
+
+static PIECE_KEYS: [u64; 12 * 64] = build_keys(0x6E65_6574_726F_6321);
+static CASTLING_KEYS: [u64; 4] = build_keys(0x0C45_7431_1E46_0001);
+static EN_PASSANT_KEYS: [u64; 8] = build_keys(0x0E9A_55A4_7000_0002);
+const BLACK_TO_MOVE: u64 = splitmix(0xB1AC_C000_0000_0003);
+
+impl Board {
+    pub fn position_key(&self) -> u64 {
+        let mut key = 0u64;
+        for (square, piece) in self.iter_pieces() {
+            if let Some(kind) = PIECE_CHARS.find(piece.get_piece_type_as_char()) {
+                key ^= PIECE_KEYS[kind * 64 + square.index()];
+            }
+        }
+        if self.turn() == Side::Black {
+            key ^= BLACK_TO_MOVE;
+        }
+        let rights = self.castling_rights();
+        for (held, right_key) in [
+            rights.white_king_side,
+            rights.white_queen_side,
+            rights.black_king_side,
+            rights.black_queen_side,
+        ]
+        .into_iter()
+        .zip(CASTLING_KEYS)
+        {
+            if held {
+                key ^= right_key;
+            }
+        }
+        if let Some(square) = self.en_passant_square() {
+            key ^= EN_PASSANT_KEYS[square.file().index()];
+        }
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::STANDARD_FEN;
+
+    #[test]
+    fn test_transpositions_share_a_key() {
+        let start = Board::from_fen(STANDARD_FEN).unwrap();
+        let play = |sans: &[&str]| sans.iter().fold(start.clone(), |board, san| board.play_san(san).unwrap());
+
+        let one = play(&["Nf3", "Nf6", "Nc3"]);
+        let other = play(&["Nc3", "Nf6", "Nf3"]);
+        assert_eq!(one.position_key(), other.position_key());
+        assert_ne!(one.position_key(), play(&["Nc3", "Nf6"]).position_key());
+
+        // same pieces, but moving the rooks gave up castling
+        let walked = play(&["Nf3", "Nf6", "Rg1", "Rg8", "Rh1", "Rh8"]);
+        assert_ne!(walked.position_key(), start.position_key());
+        let round_trip = play(&["Nf3", "Nf6", "Ng1", "Ng8"]);
+        assert_eq!(round_trip.position_key(), start.position_key());
+    }
+}